@@ -0,0 +1,489 @@
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, empty, stdin, LineWriter, Write},
+    path::Path,
+    rc::Rc,
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use func::{
+    common::{
+        ast::Statement,
+        error::{code, Error, ErrorType},
+        json::Value,
+        position::Position,
+    },
+    runtime::{hook::Hook, interpreter::Interpreter},
+};
+
+use super::{
+    debugger::{call_stack, Mode, Stepper},
+    io_error, parse,
+    protocol::{read_message, write_message},
+    read_source, status, usage_error, Options, SUCCESS,
+};
+
+// Func programs run on a single thread.
+const THREAD_ID: usize = 1;
+
+pub fn dap_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    if !args.is_empty() {
+        return Ok(usage_error("`dap` doesn't take arguments"));
+    }
+
+    // The program runs on this thread and blocks it while paused, so requests are read on
+    // another one.
+    let (sender, requests) = channel();
+    thread::spawn(move || {
+        let stdin = stdin();
+        let mut input = stdin.lock();
+        while let Ok(Some(message)) = read_message(&mut input) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    let adapter = Rc::new(RefCell::new(Adapter {
+        client: Rc::new(Client::default()),
+        requests,
+        stepper: Stepper::new(Mode::Continue),
+        reason: "breakpoint",
+        position: None,
+        launch: None,
+        configured: false,
+        disconnected: false,
+    }));
+    serve(&adapter).map_err(|err| io_error("<stdio>", err))?;
+
+    Ok(SUCCESS)
+}
+
+// Answers requests until the client disconnects, running the program once it is both
+// launched and configured.
+fn serve(adapter: &Rc<RefCell<Adapter>>) -> io::Result<()> {
+    loop {
+        let request = adapter.borrow().requests.recv();
+        let request = match request {
+            Ok(request) => request,
+            Err(..) => return Ok(()),
+        };
+        if let Flow::Disconnect = adapter.borrow_mut().handle(&request, None)? {
+            return Ok(());
+        }
+
+        let arguments = {
+            let mut adapter = adapter.borrow_mut();
+            if adapter.configured {
+                adapter.launch.take()
+            } else {
+                None
+            }
+        };
+        if let Some(arguments) = arguments {
+            run_program(adapter, &arguments)?;
+            if adapter.borrow().disconnected {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn run_program(adapter: &Rc<RefCell<Adapter>>, arguments: &Value) -> io::Result<()> {
+    let client = adapter.borrow().client.clone();
+    let source_path = arguments
+        .get("program")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    let args = arguments
+        .get("args")
+        .and_then(Value::as_array)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    if arguments.get("stopOnEntry") == Some(&Value::Bool(true)) {
+        let mut adapter = adapter.borrow_mut();
+        adapter.stepper.mode = Mode::Step;
+        adapter.reason = "entry";
+    }
+
+    let result = read_source(&source_path)
+        .and_then(|source| parse(&source_path, &source))
+        .and_then(|program| {
+            // stdin carries the protocol, so `read` sees the end of its input.
+            Interpreter::with_io(empty(), LineWriter::new(Output(client.clone())))
+                .with_args(args)
+                .with_hook(adapter.clone())
+                .interpret(program)
+        });
+
+    let exit_code = match result {
+        Ok(()) => SUCCESS,
+        Err(err) => {
+            if !matches!(err.kind(), ErrorType::Exit(..)) {
+                client.event(
+                    "output",
+                    Value::object(vec![
+                        ("category", "stderr".into()),
+                        ("output", format!("{}\n", err).into()),
+                    ]),
+                )?;
+            }
+            status(&err)
+        }
+    };
+    if adapter.borrow().disconnected {
+        return Ok(());
+    }
+    client.event(
+        "exited",
+        Value::object(vec![("exitCode", exit_code.into())]),
+    )?;
+    client.event("terminated", Value::object(Vec::new()))
+}
+
+enum Flow {
+    Stay,
+    Resume,
+    Disconnect,
+}
+
+#[derive(Default)]
+struct Client {
+    seq: Cell<usize>,
+}
+
+impl Client {
+    fn send(&self, kind: &str, mut entries: Vec<(&str, Value)>) -> io::Result<()> {
+        self.seq.set(self.seq.get() + 1);
+        let mut message = vec![("seq", self.seq.get().into()), ("type", kind.into())];
+        message.append(&mut entries);
+        write_message(&Value::object(message))
+    }
+
+    fn respond(&self, request: &Value, result: Result<Value, String>) -> io::Result<()> {
+        let mut entries = vec![
+            (
+                "request_seq",
+                request.get("seq").cloned().unwrap_or(Value::Null),
+            ),
+            (
+                "command",
+                request.get("command").cloned().unwrap_or(Value::Null),
+            ),
+            ("success", result.is_ok().into()),
+        ];
+        match result {
+            Ok(Value::Null) => {}
+            Ok(body) => entries.push(("body", body)),
+            Err(message) => entries.push(("message", message.into())),
+        }
+        self.send("response", entries)
+    }
+
+    fn event(&self, event: &str, body: Value) -> io::Result<()> {
+        self.send("event", vec![("event", event.into()), ("body", body)])
+    }
+}
+
+// Program output goes to the client as `output` events, stdout carries the protocol.
+struct Output(Rc<Client>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.event(
+            "output",
+            Value::object(vec![
+                ("category", "stdout".into()),
+                ("output", String::from_utf8_lossy(buf).into_owned().into()),
+            ]),
+        )?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Adapter {
+    client: Rc<Client>,
+    requests: Receiver<Value>,
+    stepper: Stepper,
+    // Why the program pauses next, unless it is for a breakpoint.
+    reason: &'static str,
+    // Where the program is paused, `None` while it runs.
+    position: Option<Position>,
+    launch: Option<Value>,
+    configured: bool,
+    disconnected: bool,
+}
+
+impl Hook for Adapter {
+    fn before_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        statement: &Statement,
+    ) -> Result<(), Error> {
+        // Requests that come in while the program runs, like `pause` or new breakpoints.
+        while let Ok(request) = self.requests.try_recv() {
+            if let Flow::Disconnect = self.handle(&request, None).map_err(stdio_error)? {
+                return Err(disconnected(statement));
+            }
+        }
+
+        let position = match self.stepper.pause_at(interpreter, statement) {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+        let reason = match self.stepper.mode {
+            Mode::Continue => "breakpoint",
+            _ => self.reason,
+        };
+        self.client
+            .event(
+                "stopped",
+                Value::object(vec![
+                    ("reason", reason.into()),
+                    ("threadId", THREAD_ID.into()),
+                    ("allThreadsStopped", true.into()),
+                ]),
+            )
+            .map_err(stdio_error)?;
+
+        self.position = Some(position);
+        let flow = loop {
+            let request = match self.requests.recv() {
+                Ok(request) => request,
+                Err(..) => break Flow::Disconnect,
+            };
+            match self
+                .handle(&request, Some(interpreter))
+                .map_err(stdio_error)?
+            {
+                Flow::Stay => continue,
+                flow => break flow,
+            }
+        };
+        self.position = None;
+
+        match flow {
+            Flow::Disconnect => Err(disconnected(statement)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Adapter {
+    // Answers a request, `interpreter` is only given while the program is paused.
+    fn handle(
+        &mut self,
+        request: &Value,
+        interpreter: Option<&mut Interpreter>,
+    ) -> io::Result<Flow> {
+        let command = request.get("command").and_then(Value::as_str).unwrap_or("");
+        let arguments = request.get("arguments").cloned().unwrap_or(Value::Null);
+
+        let mut flow = Flow::Stay;
+        let result = match (command, interpreter) {
+            ("initialize", _) => Ok(Value::object(vec![
+                ("supportsConfigurationDoneRequest", true.into()),
+                ("supportsEvaluateForHovers", true.into()),
+            ])),
+            ("launch", _) => {
+                self.launch = Some(arguments);
+                Ok(Value::Null)
+            }
+            ("configurationDone", _) => {
+                self.configured = true;
+                Ok(Value::Null)
+            }
+            ("setBreakpoints", _) => Ok(self.set_breakpoints(&arguments)),
+            ("threads", _) => Ok(Value::object(vec![(
+                "threads",
+                vec![Value::object(vec![
+                    ("id", THREAD_ID.into()),
+                    ("name", "main".into()),
+                ])]
+                .into(),
+            )])),
+            ("pause", _) => {
+                self.stepper.mode = Mode::Step;
+                self.reason = "pause";
+                Ok(Value::Null)
+            }
+            ("disconnect" | "terminate", _) => {
+                self.disconnected = true;
+                flow = Flow::Disconnect;
+                Ok(Value::Null)
+            }
+            ("continue" | "next" | "stepIn" | "stepOut", Some(interpreter)) => {
+                let depth = interpreter.frames().len();
+                self.stepper.mode = match command {
+                    "next" => Mode::Next(depth),
+                    "stepIn" => Mode::Step,
+                    "stepOut" => Mode::Out(depth),
+                    _ => Mode::Continue,
+                };
+                self.reason = "step";
+                flow = Flow::Resume;
+                Ok(Value::object(vec![("allThreadsContinued", true.into())]))
+            }
+            ("stackTrace", Some(interpreter)) => Ok(self.stack_trace(interpreter)),
+            ("scopes", Some(..)) => Ok(scopes(&arguments)),
+            ("variables", Some(interpreter)) => variables(interpreter, &arguments),
+            ("evaluate", Some(interpreter)) => evaluate(interpreter, &arguments),
+            ("continue" | "next" | "stepIn" | "stepOut" | "stackTrace" | "scopes", None)
+            | ("variables" | "evaluate", None) => Err("The program isn't paused".to_string()),
+            (command, _) => Err(format!("Unsupported request `{}`", command)),
+        };
+
+        self.client.respond(request, result)?;
+        if command == "initialize" {
+            self.client.event("initialized", Value::Null)?;
+        }
+        Ok(flow)
+    }
+
+    // Replaces the breakpoints of one source, every line is accepted.
+    fn set_breakpoints(&mut self, arguments: &Value) -> Value {
+        let source_path = arguments
+            .pointer("source.path")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        let lines: Vec<usize> = arguments
+            .get("breakpoints")
+            .and_then(Value::as_array)
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|breakpoint| breakpoint.get("line").and_then(Value::as_f64))
+            .map(|line| line as usize)
+            .collect();
+
+        self.stepper
+            .breakpoints
+            .retain(|(file, _)| *file != source_path);
+        let breakpoints = lines
+            .into_iter()
+            .map(|line| {
+                self.stepper.breakpoints.push((source_path.clone(), line));
+                Value::object(vec![("verified", true.into()), ("line", line.into())])
+            })
+            .collect::<Vec<_>>();
+        Value::object(vec![("breakpoints", breakpoints.into())])
+    }
+
+    // Frame ids count from the innermost frame, `0` is where the program is paused.
+    fn stack_trace(&self, interpreter: &Interpreter) -> Value {
+        let position = match &self.position {
+            Some(position) => position,
+            None => return Value::object(vec![("stackFrames", Vec::new().into())]),
+        };
+        let frames: Vec<Value> = call_stack(interpreter, position)
+            .into_iter()
+            .enumerate()
+            .map(|(id, (name, position))| {
+                let source_name = Path::new(&position.source_path)
+                    .file_name()
+                    .map_or(position.source_path.clone(), |name| {
+                        name.to_string_lossy().into_owned()
+                    });
+                Value::object(vec![
+                    ("id", id.into()),
+                    ("name", name.into()),
+                    (
+                        "source",
+                        Value::object(vec![
+                            ("name", source_name.into()),
+                            ("path", position.source_path.as_str().into()),
+                        ]),
+                    ),
+                    ("line", position.row.into()),
+                    ("column", position.column.into()),
+                ])
+            })
+            .collect();
+        let total = frames.len();
+        Value::object(vec![
+            ("stackFrames", frames.into()),
+            ("totalFrames", total.into()),
+        ])
+    }
+}
+
+// Every frame has a single scope, `variablesReference` is the frame id plus one since `0`
+// means "no variables".
+fn scopes(arguments: &Value) -> Value {
+    let frame = arguments
+        .get("frameId")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0) as usize;
+    Value::object(vec![(
+        "scopes",
+        vec![Value::object(vec![
+            ("name", "Locals".into()),
+            ("variablesReference", (frame + 1).into()),
+            ("expensive", false.into()),
+        ])]
+        .into(),
+    )])
+}
+
+fn variables(interpreter: &Interpreter, arguments: &Value) -> Result<Value, String> {
+    let reference = arguments
+        .get("variablesReference")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0) as usize;
+    let variables = reference
+        .checked_sub(1)
+        .and_then(|frame| interpreter.frame_variables(frame))
+        .ok_or_else(|| format!("Unknown variables reference {}", reference))?;
+
+    let variables: Vec<Value> = variables
+        .into_iter()
+        .map(|(name, value)| {
+            Value::object(vec![
+                ("name", name.into()),
+                ("value", value.to_string().into()),
+                ("variablesReference", 0.into()),
+            ])
+        })
+        .collect();
+    Ok(Value::object(vec![("variables", variables.into())]))
+}
+
+// Expressions see the bindings of the innermost frame, whichever frame is selected.
+fn evaluate(interpreter: &mut Interpreter, arguments: &Value) -> Result<Value, String> {
+    let expression = arguments
+        .get("expression")
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    let value = parse("<debug>", expression)
+        .and_then(|program| interpreter.evaluate(program))
+        .map_err(|err| err.to_string())?;
+    Ok(Value::object(vec![
+        ("result", value.to_string().into()),
+        ("variablesReference", 0.into()),
+    ]))
+}
+
+fn stdio_error(err: io::Error) -> Error {
+    io_error("<stdio>", err)
+}
+
+fn disconnected(statement: &Statement) -> Error {
+    Error::new(
+        ErrorType::Exit(SUCCESS),
+        code::EXIT,
+        "The client disconnected".to_string(),
+        statement
+            .position()
+            .cloned()
+            .unwrap_or_else(|| Position::new("<dap>".to_string(), 0, 0, 0)),
+    )
+}
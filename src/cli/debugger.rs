@@ -0,0 +1,293 @@
+use std::{
+    io::{stdin, stdout, Write},
+    path::Path,
+};
+
+use func::{
+    common::{
+        ast::Statement,
+        error::{code, Error, ErrorType},
+        position::Position,
+    },
+    runtime::{hook::Hook, interpreter::Interpreter},
+};
+
+use super::{parse, read_source, usage_error, Options, SUCCESS};
+
+const HELP: &str = "\
+s, step             runs until the next statement, entering calls.
+n, next             runs until the next statement in this function or its callers.
+o, out              runs until the current function returns.
+c, continue         runs until the next breakpoint.
+b, break [file:]line
+                    sets a breakpoint, without a line lists them.
+d, delete [file:]line
+                    removes a breakpoint.
+bt, backtrace       prints the call stack.
+v, vars [frame]     prints the variables of a frame, the innermost by default.
+p, print <expr>     evaluates an expression in the paused frame.
+l, list             prints the source around the current line.
+q, quit             stops the program.
+h, help             prints this message.
+Pressing enter repeats the last command.";
+
+pub fn debug_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    let (source_path, script_args) = match args.split_first() {
+        Some((source_path, script_args)) => (source_path, script_args),
+        None => return Ok(usage_error("`debug` expects a file")),
+    };
+
+    let source = read_source(source_path)?;
+    let program = parse(source_path, &source)?;
+    let debugger = Debugger {
+        source_path: source_path.clone(),
+        lines: source.lines().map(str::to_string).collect(),
+        stepper: Stepper::new(Mode::Step),
+        last_command: String::new(),
+    };
+
+    println!("Debugging {}, `help` lists the commands.", source_path);
+    Interpreter::new()
+        .with_args(script_args.to_vec())
+        .with_hook(debugger)
+        .interpret(program)?;
+    println!("Program finished.");
+
+    Ok(SUCCESS)
+}
+
+pub enum Mode {
+    Step,
+    // Pauses once the call stack is at most this deep.
+    Next(usize),
+    // Pauses once the call stack is shallower than this.
+    Out(usize),
+    Continue,
+}
+
+// Decides where a program pauses, shared by `func debug` and `func dap`.
+pub struct Stepper {
+    pub breakpoints: Vec<(String, usize)>,
+    pub mode: Mode,
+    // The depth and position of the previous statement, so a breakpoint pauses once per visit
+    // of a line instead of once per statement on it.
+    last_location: Option<(usize, String, usize)>,
+}
+
+impl Stepper {
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            mode,
+            last_location: None,
+        }
+    }
+
+    // Where to pause before `statement`, if anywhere.
+    pub fn pause_at(
+        &mut self,
+        interpreter: &Interpreter,
+        statement: &Statement,
+    ) -> Option<Position> {
+        let position = statement.position()?;
+        let depth = interpreter.frames().len();
+        let location = (depth, position.source_path.clone(), position.row);
+
+        let at_breakpoint = self.last_location.as_ref() != Some(&location)
+            && self.breakpoints.iter().any(|(file, line)| {
+                *line == position.row && same_file(file, &position.source_path)
+            });
+        self.last_location = Some(location);
+        let pause = at_breakpoint
+            || match self.mode {
+                Mode::Step => true,
+                Mode::Next(target) => depth <= target,
+                Mode::Out(target) => depth < target,
+                Mode::Continue => false,
+            };
+
+        if pause {
+            Some(position.clone())
+        } else {
+            None
+        }
+    }
+}
+
+struct Debugger {
+    source_path: String,
+    lines: Vec<String>,
+    stepper: Stepper,
+    last_command: String,
+}
+
+impl Hook for Debugger {
+    fn before_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        statement: &Statement,
+    ) -> Result<(), Error> {
+        match self.stepper.pause_at(interpreter, statement) {
+            Some(position) => self.pause(interpreter, &position),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Debugger {
+    fn pause(&mut self, interpreter: &mut Interpreter, position: &Position) -> Result<(), Error> {
+        let name = interpreter
+            .frames()
+            .last()
+            .map_or("<main>", |frame| frame.name.as_str());
+        println!(
+            "Paused in {} at {}:{}",
+            name, position.source_path, position.row
+        );
+        self.print_line(position.row, true);
+
+        loop {
+            print!("(debug) ");
+            stdout().flush().ok();
+            let mut line = String::new();
+            if stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                // Without a terminal to read from, the program just runs to the end.
+                self.stepper.mode = Mode::Continue;
+                self.stepper.breakpoints.clear();
+                return Ok(());
+            }
+            let mut line = line.trim().to_string();
+            if line.is_empty() {
+                line = self.last_command.clone();
+            }
+            self.last_command = line.clone();
+
+            let (command, argument) = match line.find(char::is_whitespace) {
+                Some(index) => (&line[..index], line[index..].trim()),
+                None => (line.as_str(), ""),
+            };
+            let depth = interpreter.frames().len();
+            match command {
+                "s" | "step" => self.stepper.mode = Mode::Step,
+                "n" | "next" => self.stepper.mode = Mode::Next(depth),
+                "o" | "out" => self.stepper.mode = Mode::Out(depth),
+                "c" | "continue" => self.stepper.mode = Mode::Continue,
+                "b" | "break" if argument.is_empty() => {
+                    for (file, line) in &self.stepper.breakpoints {
+                        println!("{}:{}", file, line);
+                    }
+                    continue;
+                }
+                "b" | "break" => {
+                    match self.breakpoint(argument) {
+                        Some(breakpoint) => {
+                            println!("Breakpoint set at {}:{}", breakpoint.0, breakpoint.1);
+                            self.stepper.breakpoints.push(breakpoint);
+                        }
+                        None => println!("Expected `[file:]line`, found `{}`", argument),
+                    }
+                    continue;
+                }
+                "d" | "delete" => {
+                    match self.breakpoint(argument) {
+                        Some(breakpoint) => self
+                            .stepper
+                            .breakpoints
+                            .retain(|other| *other != breakpoint),
+                        None => println!("Expected `[file:]line`, found `{}`", argument),
+                    }
+                    continue;
+                }
+                "bt" | "backtrace" => {
+                    for (depth, (name, location)) in
+                        call_stack(interpreter, position).into_iter().enumerate()
+                    {
+                        println!(
+                            "#{} {} at {}:{}",
+                            depth, name, location.source_path, location.row
+                        );
+                    }
+                    continue;
+                }
+                "v" | "vars" => {
+                    let frame = argument.parse().unwrap_or(0);
+                    match interpreter.frame_variables(frame) {
+                        Some(variables) => {
+                            for (name, value) in variables {
+                                println!("{} = {}", name, value);
+                            }
+                        }
+                        None => println!("There is no frame #{}", frame),
+                    }
+                    continue;
+                }
+                "p" | "print" => {
+                    match parse("<debug>", argument)
+                        .and_then(|program| interpreter.evaluate(program))
+                    {
+                        Ok(value) => println!("{}", value),
+                        Err(err) => err.report(),
+                    }
+                    continue;
+                }
+                "l" | "list" => {
+                    let first = position.row.saturating_sub(3).max(1);
+                    for row in first..=position.row + 3 {
+                        self.print_line(row, row == position.row);
+                    }
+                    continue;
+                }
+                "q" | "quit" => {
+                    return Err(Error::new(
+                        ErrorType::Exit(SUCCESS),
+                        code::EXIT,
+                        "The debugger stopped the program".to_string(),
+                        position.clone(),
+                    ))
+                }
+                "h" | "help" => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                command => {
+                    println!("Unknown command `{}`, `help` lists the commands.", command);
+                    continue;
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    fn breakpoint(&self, argument: &str) -> Option<(String, usize)> {
+        let (file, line) = match argument.rsplit_once(':') {
+            Some((file, line)) => (file.to_string(), line),
+            None => (self.source_path.clone(), argument),
+        };
+        line.parse().ok().map(|line| (file, line))
+    }
+
+    fn print_line(&self, row: usize, current: bool) {
+        if let Some(line) = row.checked_sub(1).and_then(|index| self.lines.get(index)) {
+            println!("{} {:>4} | {}", if current { ">" } else { " " }, row, line);
+        }
+    }
+}
+
+// The functions on the call stack with where each one is, innermost first.
+pub fn call_stack<'a>(
+    interpreter: &'a Interpreter,
+    position: &'a Position,
+) -> Vec<(&'a str, &'a Position)> {
+    let mut stack = Vec::new();
+    let mut position = position;
+    for frame in interpreter.frames().iter().rev() {
+        stack.push((frame.name.as_str(), position));
+        position = &frame.position;
+    }
+    stack.push(("<main>", position));
+    stack
+}
+
+pub fn same_file(breakpoint: &str, source_path: &str) -> bool {
+    breakpoint == source_path || Path::new(source_path).ends_with(breakpoint)
+}
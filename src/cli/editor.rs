@@ -0,0 +1,336 @@
+use std::{
+    fs::{read_to_string, OpenOptions},
+    io::{self, stdin, stdout, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use super::terminal::{is_terminal, STDIN};
+
+const HISTORY_SIZE: usize = 1000;
+
+pub enum ReadResult {
+    Line(String),
+    Interrupted,
+    Eof,
+}
+
+// Supplies candidates for the word before the cursor and a hint shown after the line.
+pub trait Completer {
+    // Returns where the completed word starts, in characters, and its candidates.
+    fn complete(&self, line: &str) -> (usize, Vec<String>);
+    fn hint(&self, line: &str) -> Option<String>;
+}
+
+enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Interrupt,
+    Eof,
+    Ignored,
+}
+
+// Puts the terminal in non canonical mode while a line is being edited and restores it
+// when dropped, so the program being evaluated sees a normal terminal.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn stty(args: &[&str]) -> io::Result<String> {
+        let output = Command::new("stty")
+            .args(args)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::null())
+            .output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(io::Error::other("stty failed"))
+        }
+    }
+
+    fn enable() -> io::Result<Self> {
+        let saved = Self::stty(&["-g"])?;
+        Self::stty(&["-icanon", "-echo", "-isig", "-ixon", "min", "1"])?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        Self::stty(&[&self.saved]).ok();
+    }
+}
+
+pub struct Editor {
+    history: Vec<String>,
+    history_path: Option<PathBuf>,
+    interactive: bool,
+}
+
+impl Editor {
+    pub fn new(history_path: Option<PathBuf>) -> Self {
+        let history = history_path
+            .as_ref()
+            .and_then(|path| read_to_string(path).ok())
+            .map(|history| history.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Self {
+            history,
+            history_path,
+            interactive: is_terminal(STDIN),
+        }
+    }
+
+    pub fn add_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if self.history.len() > HISTORY_SIZE {
+            self.history.remove(0);
+        }
+
+        if let Some(path) = &self.history_path {
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                writeln!(file, "{}", line).ok();
+            }
+        }
+    }
+
+    pub fn read_line(&mut self, prompt: &str, completer: &dyn Completer) -> io::Result<ReadResult> {
+        if self.interactive {
+            if let Ok(raw_mode) = RawMode::enable() {
+                let result = self.edit_line(prompt, completer);
+                drop(raw_mode);
+                return result;
+            }
+        }
+
+        print!("{}", prompt);
+        stdout().flush()?;
+        let mut line = String::new();
+        if stdin().read_line(&mut line)? == 0 {
+            Ok(ReadResult::Eof)
+        } else {
+            Ok(ReadResult::Line(
+                line.trim_end_matches(&['\n', '\r'][..]).to_string(),
+            ))
+        }
+    }
+
+    fn edit_line(&mut self, prompt: &str, completer: &dyn Completer) -> io::Result<ReadResult> {
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // Browsing the history keeps the line being typed at the end.
+        let mut history_index = self.history.len();
+        let mut draft = String::new();
+
+        self.render(prompt, &line, cursor, completer)?;
+        loop {
+            match read_key()? {
+                Key::Char(character) => {
+                    line.insert(cursor, character);
+                    cursor += 1;
+                }
+                Key::Tab => {
+                    let before: String = line[..cursor].iter().collect();
+                    let (start, candidates) = completer.complete(&before);
+                    let common = common_prefix(&candidates);
+                    if common.chars().count() > cursor - start {
+                        let rest: Vec<char> = line.split_off(cursor);
+                        line.truncate(start);
+                        line.extend(common.chars());
+                        cursor = line.len();
+                        line.extend(rest);
+                    } else if candidates.len() > 1 {
+                        print!("\r\n{}\r\n", candidates.join("  "));
+                    }
+                }
+                Key::Enter => {
+                    print!("\r\n");
+                    stdout().flush()?;
+                    return Ok(ReadResult::Line(line.into_iter().collect()));
+                }
+                Key::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                Key::Delete if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                Key::Left if cursor > 0 => cursor -= 1,
+                Key::Right if cursor < line.len() => cursor += 1,
+                Key::Home => cursor = 0,
+                Key::End => cursor = line.len(),
+                Key::Up if history_index > 0 => {
+                    if history_index == self.history.len() {
+                        draft = line.iter().collect();
+                    }
+                    history_index -= 1;
+                    line = self.history[history_index].chars().collect();
+                    cursor = line.len();
+                }
+                Key::Down if history_index < self.history.len() => {
+                    history_index += 1;
+                    line = match self.history.get(history_index) {
+                        Some(entry) => entry.chars().collect(),
+                        None => draft.chars().collect(),
+                    };
+                    cursor = line.len();
+                }
+                Key::Interrupt => {
+                    print!("^C\r\n");
+                    stdout().flush()?;
+                    return Ok(ReadResult::Interrupted);
+                }
+                Key::Eof if line.is_empty() => {
+                    print!("\r\n");
+                    stdout().flush()?;
+                    return Ok(ReadResult::Eof);
+                }
+                Key::Eof if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                _ => {}
+            }
+            self.render(prompt, &line, cursor, completer)?;
+        }
+    }
+
+    fn render(
+        &self,
+        prompt: &str,
+        line: &[char],
+        cursor: usize,
+        completer: &dyn Completer,
+    ) -> io::Result<()> {
+        let at_end = cursor == line.len();
+        let line: String = line.iter().collect();
+        let column = prompt.chars().count() + cursor;
+        print!("\r{}{}", prompt, line);
+        // The hint is dimmed and only shown while typing at the end of the line.
+        if let Some(hint) = completer.hint(&line).filter(|_| at_end) {
+            print!("  \x1b[2m{}\x1b[0m", hint);
+        }
+        print!("\x1b[K\r");
+        if column > 0 {
+            print!("\x1b[{}C", column);
+        }
+        stdout().flush()
+    }
+}
+
+fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix: Vec<char> = match candidates.first() {
+        Some(first) => first.chars().collect(),
+        None => return String::new(),
+    };
+    for candidate in &candidates[1..] {
+        let shared = prefix
+            .iter()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| **a == *b)
+            .count();
+        prefix.truncate(shared);
+    }
+    prefix.into_iter().collect()
+}
+
+fn read_byte() -> io::Result<Option<u8>> {
+    let mut byte = [0u8];
+    match stdin().read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+fn read_key() -> io::Result<Key> {
+    let byte = match read_byte()? {
+        Some(byte) => byte,
+        None => return Ok(Key::Eof),
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        127 | 8 => Key::Backspace,
+        1 => Key::Home,
+        5 => Key::End,
+        2 => Key::Left,
+        6 => Key::Right,
+        16 => Key::Up,
+        14 => Key::Down,
+        b'\t' => Key::Tab,
+        3 => Key::Interrupt,
+        4 => Key::Eof,
+        27 => read_escape()?,
+        byte if byte < 32 => Key::Ignored,
+        byte => read_char(byte)?,
+    };
+    Ok(key)
+}
+
+fn read_escape() -> io::Result<Key> {
+    let key = match (read_byte()?, read_byte()?) {
+        (Some(b'['), Some(b'A')) | (Some(b'O'), Some(b'A')) => Key::Up,
+        (Some(b'['), Some(b'B')) | (Some(b'O'), Some(b'B')) => Key::Down,
+        (Some(b'['), Some(b'C')) | (Some(b'O'), Some(b'C')) => Key::Right,
+        (Some(b'['), Some(b'D')) | (Some(b'O'), Some(b'D')) => Key::Left,
+        (Some(b'['), Some(b'H')) | (Some(b'O'), Some(b'H')) => Key::Home,
+        (Some(b'['), Some(b'F')) | (Some(b'O'), Some(b'F')) => Key::End,
+        (Some(b'['), Some(digit)) if digit.is_ascii_digit() => {
+            // Sequences like `ESC [ 3 ~` end with a tilde.
+            let mut last = digit;
+            while last != b'~' {
+                match read_byte()? {
+                    Some(byte) => last = byte,
+                    None => break,
+                }
+            }
+            match digit {
+                b'1' | b'7' => Key::Home,
+                b'4' | b'8' => Key::End,
+                b'3' => Key::Delete,
+                _ => Key::Ignored,
+            }
+        }
+        _ => Key::Ignored,
+    };
+    Ok(key)
+}
+
+fn read_char(first: u8) -> io::Result<Key> {
+    let length = match first {
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..length {
+        match read_byte()? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+    Ok(
+        match std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|string| string.chars().next())
+        {
+            Some(character) => Key::Char(character),
+            None => Key::Ignored,
+        },
+    )
+}
@@ -0,0 +1,550 @@
+use std::{
+    collections::HashMap,
+    io::{self, stdin, BufRead},
+};
+
+use func::{
+    common::{
+        error::Error,
+        json::Value,
+        position::Position,
+        token::{Token, TokenType},
+    },
+    frontend::{lexer::Lexer, resolver::Resolver},
+    runtime::stdlib,
+};
+
+use super::{
+    io_error, lex, parse,
+    protocol::{read_message, write_message},
+    usage_error, Options, FAILURE, KEYWORD_SIGNATURES, SUCCESS,
+};
+
+// JSON-RPC error codes the server answers with.
+const PARSE_ERROR: i32 = -32700;
+const INVALID_PARAMS: i32 = -32602;
+const METHOD_NOT_FOUND: i32 = -32601;
+
+// LSP enumerations.
+const FULL_SYNC: usize = 1;
+const SEVERITY_ERROR: usize = 1;
+const SYMBOL_FUNCTION: usize = 12;
+const SYMBOL_VARIABLE: usize = 13;
+const COMPLETION_FUNCTION: usize = 3;
+const COMPLETION_VARIABLE: usize = 6;
+const COMPLETION_KEYWORD: usize = 14;
+
+pub fn lsp_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    if !args.is_empty() {
+        return Ok(usage_error("`lsp` doesn't take arguments"));
+    }
+
+    let stdin = stdin();
+    let mut server = Server {
+        input: stdin.lock(),
+        documents: HashMap::new(),
+        shutdown: false,
+    };
+    server.run().map_err(|err| io_error("<stdio>", err))
+}
+
+#[derive(PartialEq)]
+enum SymbolKind {
+    Function,
+    Variable,
+    Paramiter,
+}
+
+struct Symbol {
+    kind: SymbolKind,
+    token: Token,
+    detail: String,
+    top_level: bool,
+}
+
+// What the server knows about one document, rebuilt from its text on every request.
+struct Analysis {
+    tokens: Vec<Token>,
+    symbols: Vec<Symbol>,
+}
+
+impl Analysis {
+    fn new(uri: &str, text: &str) -> Self {
+        let tokens = Lexer::new(uri.to_string(), text).lex().unwrap_or_default();
+        let symbols = Self::symbols(&tokens);
+        Self { tokens, symbols }
+    }
+
+    // Declarations are found from the tokens, so they are available even while the file
+    // doesn't parse.
+    fn symbols(tokens: &[Token]) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        let mut depth = 0;
+
+        for (index, token) in tokens.iter().enumerate() {
+            match token.ttype {
+                TokenType::OpenCurly => {
+                    depth += 1;
+                    continue;
+                }
+                TokenType::CloseCurly => {
+                    depth -= 1;
+                    continue;
+                }
+                TokenType::Func | TokenType::Let => {}
+                _ => continue,
+            }
+            let name = match tokens.get(index + 1) {
+                Some(name) if name.ttype == TokenType::Identifier => name,
+                _ => continue,
+            };
+
+            if token.ttype == TokenType::Let {
+                symbols.push(Symbol {
+                    kind: SymbolKind::Variable,
+                    token: name.clone(),
+                    detail: format!("let {}", name.lexeme),
+                    top_level: depth == 0,
+                });
+                continue;
+            }
+
+            let paramiters: Vec<&Token> = tokens[index + 2..]
+                .iter()
+                .skip(1)
+                .take_while(|token| token.ttype != TokenType::CloseParen)
+                .filter(|token| token.ttype == TokenType::Identifier)
+                .collect();
+            let names: Vec<&str> = paramiters
+                .iter()
+                .map(|paramiter| paramiter.lexeme.as_str())
+                .collect();
+            symbols.push(Symbol {
+                kind: SymbolKind::Function,
+                token: name.clone(),
+                detail: format!("func {}({})", name.lexeme, names.join(", ")),
+                top_level: depth == 0,
+            });
+            for paramiter in paramiters {
+                symbols.push(Symbol {
+                    kind: SymbolKind::Paramiter,
+                    token: paramiter.clone(),
+                    detail: format!("paramiter {} of {}", paramiter.lexeme, name.lexeme),
+                    top_level: false,
+                });
+            }
+        }
+
+        symbols
+    }
+
+    // The token under a zero based line and character, the end of a token counts too.
+    fn token_at(&self, params: &Value) -> Option<usize> {
+        let line = params.pointer("position.line")?.as_f64()? as usize;
+        let character = params.pointer("position.character")?.as_f64()? as usize;
+        self.tokens.iter().position(|token| {
+            let start = token.position.column.saturating_sub(1);
+            token.ttype != TokenType::EOF
+                && token.position.row == line + 1
+                && start <= character
+                && character <= start + token.position.length
+        })
+    }
+
+    // With dynamic scoping a name can't be resolved statically, so calls refer to functions
+    // and so does a bare name, like `map(xs, double)`, when only a function is declared with
+    // it. Every other use of a name refers to the variables and paramiters called that.
+    fn is_function(&self, index: usize) -> bool {
+        if self.tokens.get(index + 1).map(|token| &token.ttype) == Some(&TokenType::OpenParen)
+            || index > 0 && self.tokens[index - 1].ttype == TokenType::Func
+        {
+            return true;
+        }
+        let name = &self.tokens[index].lexeme;
+        let named = || {
+            self.symbols
+                .iter()
+                .filter(|symbol| &symbol.token.lexeme == name)
+        };
+        named().any(|symbol| symbol.kind == SymbolKind::Function)
+            && named().all(|symbol| symbol.kind == SymbolKind::Function)
+    }
+
+    fn declarations(&self, index: usize) -> Vec<&Symbol> {
+        let name = &self.tokens[index].lexeme;
+        let function = self.is_function(index);
+        self.symbols
+            .iter()
+            .filter(|symbol| {
+                &symbol.token.lexeme == name && (symbol.kind == SymbolKind::Function) == function
+            })
+            .collect()
+    }
+
+    // Prefers the closest declaration above the use, a function may use a global declared
+    // further down.
+    fn definition(&self, index: usize) -> Option<&Symbol> {
+        let position = &self.tokens[index].position;
+        let declarations = self.declarations(index);
+        declarations
+            .iter()
+            .rev()
+            .find(|symbol| {
+                (symbol.token.position.row, symbol.token.position.column)
+                    <= (position.row, position.column)
+            })
+            .or_else(|| declarations.first())
+            .copied()
+    }
+
+    fn references(&self, index: usize) -> Vec<&Token> {
+        let name = &self.tokens[index].lexeme;
+        let function = self.is_function(index);
+        self.tokens
+            .iter()
+            .enumerate()
+            .filter(|(other, token)| {
+                token.ttype == TokenType::Identifier
+                    && &token.lexeme == name
+                    && self.is_function(*other) == function
+            })
+            .map(|(_, token)| token)
+            .collect()
+    }
+}
+
+fn range(position: &Position) -> Value {
+    let line = position.row.saturating_sub(1);
+    let start = position.column.saturating_sub(1);
+    Value::object(vec![
+        (
+            "start",
+            Value::object(vec![("line", line.into()), ("character", start.into())]),
+        ),
+        (
+            "end",
+            Value::object(vec![
+                ("line", line.into()),
+                ("character", (start + position.length).into()),
+            ]),
+        ),
+    ])
+}
+
+fn location(uri: &str, position: &Position) -> Value {
+    Value::object(vec![("uri", uri.into()), ("range", range(position))])
+}
+
+fn diagnostic(err: &Error) -> Value {
+    Value::object(vec![
+        ("range", range(err.position())),
+        ("severity", SEVERITY_ERROR.into()),
+        ("code", err.code().into()),
+        ("source", "func".into()),
+        ("message", err.message().into()),
+    ])
+}
+
+struct Server<R: BufRead> {
+    input: R,
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl<R: BufRead> Server<R> {
+    fn run(&mut self) -> io::Result<i32> {
+        while let Some(message) = read_message(&mut self.input)? {
+            let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+            match message.get("id") {
+                _ if message == Value::Null => {
+                    self.respond(Value::Null, Err((PARSE_ERROR, "Invalid JSON".to_string())))?
+                }
+                _ if method == "exit" => return Ok(if self.shutdown { SUCCESS } else { FAILURE }),
+                Some(id) => {
+                    let result = self.request(method, &params);
+                    self.respond(id.clone(), result)?;
+                }
+                None => self.notification(method, &params)?,
+            }
+        }
+
+        Ok(if self.shutdown { SUCCESS } else { FAILURE })
+    }
+
+    fn send(&self, message: Value) -> io::Result<()> {
+        write_message(&message)
+    }
+
+    fn respond(&self, id: Value, result: Result<Value, (i32, String)>) -> io::Result<()> {
+        let outcome = match result {
+            Ok(result) => ("result", result),
+            Err((code, message)) => (
+                "error",
+                Value::object(vec![("code", code.into()), ("message", message.into())]),
+            ),
+        };
+        self.send(Value::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("id", id),
+            outcome,
+        ]))
+    }
+
+    fn notify(&self, method: &str, params: Value) -> io::Result<()> {
+        self.send(Value::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", method.into()),
+            ("params", params),
+        ]))
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let uri = match params.pointer("textDocument.uri").and_then(Value::as_str) {
+            Some(uri) => uri.to_string(),
+            None => return Ok(()),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params
+                    .pointer("textDocument.text")
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                self.documents.insert(uri.clone(), text.to_string());
+            }
+            "textDocument/didChange" => {
+                // The server asks for full syncs, so the last change holds the whole text.
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let Some(text) = text {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+            }
+            _ => return Ok(()),
+        }
+
+        self.publish_diagnostics(&uri)
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> io::Result<()> {
+        let errors = match self.documents.get(uri) {
+            Some(text) => match parse(uri, text) {
+                Ok(program) => Resolver::new().resolve(&program),
+                Err(err) => vec![err],
+            },
+            None => Vec::new(),
+        };
+
+        self.notify(
+            "textDocument/publishDiagnostics",
+            Value::object(vec![
+                ("uri", uri.into()),
+                (
+                    "diagnostics",
+                    errors.iter().map(diagnostic).collect::<Vec<_>>().into(),
+                ),
+            ]),
+        )
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i32, String)> {
+        let uri = params
+            .pointer("textDocument.uri")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let analysis = || Analysis::new(uri, self.documents.get(uri).map_or("", String::as_str));
+
+        match method {
+            "initialize" => Ok(Self::capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/definition" => {
+                let analysis = analysis();
+                Ok(analysis
+                    .token_at(params)
+                    .filter(|index| analysis.tokens[*index].ttype == TokenType::Identifier)
+                    .and_then(|index| analysis.definition(index))
+                    .map_or(Value::Null, |symbol| location(uri, &symbol.token.position)))
+            }
+            "textDocument/hover" => Ok(Self::hover(&analysis(), params)),
+            "textDocument/documentSymbol" => Ok(analysis()
+                .symbols
+                .iter()
+                .filter(|symbol| symbol.top_level)
+                .map(|symbol| {
+                    let kind = match symbol.kind {
+                        SymbolKind::Function => SYMBOL_FUNCTION,
+                        _ => SYMBOL_VARIABLE,
+                    };
+                    Value::object(vec![
+                        ("name", symbol.token.lexeme.as_str().into()),
+                        ("kind", kind.into()),
+                        ("location", location(uri, &symbol.token.position)),
+                    ])
+                })
+                .collect::<Vec<_>>()
+                .into()),
+            "textDocument/completion" => Ok(Self::completion(&analysis())),
+            "textDocument/rename" => Self::rename(&analysis(), uri, params),
+            _ => Err((
+                METHOD_NOT_FOUND,
+                format!("Method `{}` isn't supported", method),
+            )),
+        }
+    }
+
+    fn capabilities() -> Value {
+        Value::object(vec![
+            (
+                "capabilities",
+                Value::object(vec![
+                    ("textDocumentSync", FULL_SYNC.into()),
+                    ("definitionProvider", true.into()),
+                    ("hoverProvider", true.into()),
+                    ("documentSymbolProvider", true.into()),
+                    ("renameProvider", true.into()),
+                    (
+                        "completionProvider",
+                        Value::object(vec![("triggerCharacters", Value::Array(Vec::new()))]),
+                    ),
+                ]),
+            ),
+            (
+                "serverInfo",
+                Value::object(vec![
+                    ("name", "func".into()),
+                    ("version", env!("CARGO_PKG_VERSION").into()),
+                ]),
+            ),
+        ])
+    }
+
+    fn hover(analysis: &Analysis, params: &Value) -> Value {
+        let index = match analysis.token_at(params) {
+            Some(index) => index,
+            None => return Value::Null,
+        };
+        let token = &analysis.tokens[index];
+
+        let detail = if token.ttype == TokenType::Identifier {
+            match analysis.definition(index) {
+                Some(symbol) => Some(symbol.detail.clone()),
+                None if analysis.is_function(index) => stdlib::find(&token.lexeme)
+                    .map(|builtin| format!("func {}", builtin.signature())),
+                None => None,
+            }
+        } else {
+            KEYWORD_SIGNATURES
+                .iter()
+                .find(|(keyword, _)| *keyword == token.lexeme)
+                .map(|(_, signature)| signature.to_string())
+        };
+
+        match detail {
+            Some(detail) => Value::object(vec![
+                (
+                    "contents",
+                    Value::object(vec![
+                        ("kind", "markdown".into()),
+                        ("value", format!("```func\n{}\n```", detail).into()),
+                    ]),
+                ),
+                ("range", range(&token.position)),
+            ]),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(analysis: &Analysis) -> Value {
+        let mut items = Vec::new();
+        let mut seen = Vec::new();
+        let mut add = |label: &str, kind: usize, detail: String| {
+            if !seen.contains(&label.to_string()) {
+                seen.push(label.to_string());
+                items.push(Value::object(vec![
+                    ("label", label.into()),
+                    ("kind", kind.into()),
+                    ("detail", detail.into()),
+                ]));
+            }
+        };
+
+        for symbol in &analysis.symbols {
+            let kind = match symbol.kind {
+                SymbolKind::Function => COMPLETION_FUNCTION,
+                _ => COMPLETION_VARIABLE,
+            };
+            add(&symbol.token.lexeme, kind, symbol.detail.clone());
+        }
+        for builtin in stdlib::builtins() {
+            add(
+                builtin.name,
+                COMPLETION_FUNCTION,
+                format!("func {}", builtin.signature()),
+            );
+        }
+        for (name, value) in stdlib::constants() {
+            add(
+                name,
+                COMPLETION_VARIABLE,
+                format!("let {} = {}", name, value),
+            );
+        }
+        for keyword in Lexer::keywords() {
+            let detail = KEYWORD_SIGNATURES
+                .iter()
+                .find(|(name, _)| *name == keyword)
+                .map_or("keyword", |(_, signature)| signature);
+            add(keyword, COMPLETION_KEYWORD, detail.to_string());
+        }
+
+        Value::Array(items)
+    }
+
+    fn rename(analysis: &Analysis, uri: &str, params: &Value) -> Result<Value, (i32, String)> {
+        let new_name = params.get("newName").and_then(Value::as_str).unwrap_or("");
+        let is_identifier = matches!(
+            lex(uri, new_name).as_deref(),
+            Ok([token, eof]) if token.ttype == TokenType::Identifier && eof.ttype == TokenType::EOF
+        );
+        if !is_identifier {
+            return Err((INVALID_PARAMS, format!("`{}` isn't a valid name", new_name)));
+        }
+
+        let index = analysis
+            .token_at(params)
+            .filter(|index| analysis.tokens[*index].ttype == TokenType::Identifier)
+            .filter(|index| analysis.definition(*index).is_some())
+            .ok_or((
+                INVALID_PARAMS,
+                "Only names declared in this file can be renamed".to_string(),
+            ))?;
+
+        let edits: Vec<Value> = analysis
+            .references(index)
+            .into_iter()
+            .map(|token| {
+                Value::object(vec![
+                    ("range", range(&token.position)),
+                    ("newText", new_name.into()),
+                ])
+            })
+            .collect();
+
+        Ok(Value::object(vec![(
+            "changes",
+            Value::Object(vec![(uri.to_string(), edits.into())]),
+        )]))
+    }
+}
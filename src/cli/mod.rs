@@ -0,0 +1,595 @@
+mod dap;
+mod debugger;
+mod editor;
+mod lsp;
+mod protocol;
+mod repl;
+mod terminal;
+mod testing;
+
+use std::{
+    cell::RefCell,
+    fs::{read_to_string, write},
+    io::{stderr, stdin, Read},
+    rc::Rc,
+    str::FromStr,
+    time::Duration,
+};
+
+use func::{
+    common::{
+        ast::Program,
+        error::{code, Error, ErrorType},
+        position::Position,
+        token::Token,
+    },
+    frontend::{
+        cst::SyntaxNode, formatter::Formatter, lexer::Lexer, parser::Parser, resolver::Resolver,
+    },
+    runtime::{
+        coverage::Coverage,
+        interpreter::Interpreter,
+        limits::{Capabilities, Limits},
+        profiler::Profiler,
+        tracer::Tracer,
+    },
+};
+
+pub const SUCCESS: i32 = 0;
+pub const FAILURE: i32 = 1;
+pub const USAGE: i32 = 2;
+pub const LEXING_ERROR: i32 = 3;
+pub const PARSING_ERROR: i32 = 4;
+pub const RUNTIME_ERROR: i32 = 5;
+
+// Where descriptions start in `--help`.
+const HELP_COLUMN: usize = 36;
+
+// How many of the slowest lines `run --profile` lists.
+const PROFILE_LINES: usize = 10;
+
+// Keyword statements aren't functions, so their signatures are spelled out here.
+pub const KEYWORD_SIGNATURES: &[(&str, &str)] = &[
+    ("read", "read(variable)"),
+    ("write", "write(value, ...)"),
+    ("push", "push(value, array)"),
+    ("pop", "pop(array)"),
+    ("assert", "assert(condition, message?)"),
+    ("assert_eq", "assert_eq(left, right)"),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn report(self, err: &Error) {
+        match self {
+            Self::Human => err.report(),
+            Self::Json => eprintln!("{}", err.to_json()),
+        }
+    }
+}
+
+pub struct Options {
+    pub error_format: ErrorFormat,
+}
+
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    description: &'static str,
+    // Flags and what they do, a `\n` in the description continues it on the next line.
+    options: &'static [(&'static str, &'static str)],
+    run: fn(&[String], &Options) -> Result<i32, Error>,
+}
+
+const COVERAGE_OPTION: (&str, &str) = (
+    "--coverage[=path]",
+    "prints line and branch coverage and writes LCOV\nto path (default lcov.info).",
+);
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "run",
+        usage: "func run [options] <file> [args...]",
+        description: "runs a script, `<file>` can be `-` to read it from stdin or `-e code` to take it inline.",
+        options: &[
+            (
+                "--profile[=path]",
+                "prints timings and writes folded stacks to path\n(default profile.folded).",
+            ),
+            COVERAGE_OPTION,
+            (
+                "--trace[=functions]",
+                "logs statements and calls (of the comma separated\nfunctions) to stderr.",
+            ),
+            ("--max-steps=n", "stops the script after n steps."),
+            (
+                "--max-depth=n",
+                "stops the script when calls nest deeper than n.",
+            ),
+            (
+                "--max-size=n",
+                "stops the script when a string or array grows\nlarger than n.",
+            ),
+            ("--timeout=ms", "stops the script after ms milliseconds."),
+            ("--sandbox", "denies `read`, environment and file access."),
+        ],
+        run: run_command,
+    },
+    Command {
+        name: "debug",
+        usage: "func debug <file> [args...]",
+        description:
+            "runs a script under a step debugger, `help` at its prompt lists the commands.",
+        options: &[],
+        run: debugger::debug_command,
+    },
+    Command {
+        name: "repl",
+        usage: "func repl",
+        description: "starts an interactive session.",
+        options: &[],
+        run: repl_command,
+    },
+    Command {
+        name: "check",
+        usage: "func check <file>...",
+        description: "lexes, parses and resolves names without running anything.",
+        options: &[],
+        run: check_command,
+    },
+    Command {
+        name: "tokens",
+        usage: "func tokens <file>",
+        description: "prints the tokens the lexer produces.",
+        options: &[],
+        run: tokens_command,
+    },
+    Command {
+        name: "ast",
+        usage: "func ast [--cst] <file>",
+        description: "prints the syntax tree the parser produces.",
+        options: &[("--cst", "prints the lossless syntax tree instead.")],
+        run: ast_command,
+    },
+    Command {
+        name: "fmt",
+        usage: "func fmt [--check] <file>...",
+        description: "rewrites files in canonical style.",
+        options: &[(
+            "--check",
+            "only lists the files it would change, writes none.",
+        )],
+        run: fmt_command,
+    },
+    Command {
+        name: "test",
+        usage: "func test [options] [path]",
+        description: "runs the `test` blocks of every .func file in path (defaults to `.`).",
+        options: &[COVERAGE_OPTION],
+        run: testing::test_command,
+    },
+    Command {
+        name: "lsp",
+        usage: "func lsp",
+        description: "serves the Language Server Protocol over stdin and stdout.",
+        options: &[],
+        run: lsp::lsp_command,
+    },
+    Command {
+        name: "dap",
+        usage: "func dap",
+        description: "serves the Debug Adapter Protocol over stdin and stdout.",
+        options: &[],
+        run: dap::dap_command,
+    },
+];
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut options = Options {
+        error_format: ErrorFormat::Human,
+    };
+
+    let mut args = args.as_slice();
+    while let Some(arg) = args.first() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_usage();
+                return SUCCESS;
+            }
+            "-V" | "--version" => {
+                println!("func {}", env!("CARGO_PKG_VERSION"));
+                return SUCCESS;
+            }
+            "--error-format=human" => options.error_format = ErrorFormat::Human,
+            "--error-format=json" => options.error_format = ErrorFormat::Json,
+            "-" | "-e" => break,
+            arg if arg.starts_with('-') => {
+                return usage_error(&format!("Unknown option `{}`", arg))
+            }
+            _ => break,
+        }
+        args = &args[1..];
+    }
+
+    let stdin_args = ["-".to_string()];
+    let (command, args) = match args.split_first() {
+        // A script piped into `func` runs instead of the REPL.
+        None if !terminal::is_terminal(terminal::STDIN) => {
+            (find_command("run").unwrap(), &stdin_args[..])
+        }
+        None => (find_command("repl").unwrap(), args),
+        Some((name, rest)) if name == "help" => {
+            return match rest.first() {
+                None => {
+                    print_usage();
+                    SUCCESS
+                }
+                Some(name) => match find_command(name) {
+                    Some(command) => {
+                        print_command_usage(command);
+                        SUCCESS
+                    }
+                    None => usage_error(&format!("Unknown command `{}`", name)),
+                },
+            };
+        }
+        Some((name, rest)) => match find_command(name) {
+            Some(command) => (command, rest),
+            // `func <file>` is short for `func run <file>`.
+            None => (find_command("run").unwrap(), args),
+        },
+    };
+
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        print_command_usage(command);
+        return SUCCESS;
+    }
+
+    match (command.run)(args, &options) {
+        Ok(status) => status,
+        Err(err) => {
+            if !matches!(err.kind(), ErrorType::Exit(..)) {
+                options.error_format.report(&err);
+            }
+            status(&err)
+        }
+    }
+}
+
+pub fn status(err: &Error) -> i32 {
+    match err.kind() {
+        ErrorType::LexingError => LEXING_ERROR,
+        ErrorType::ParsingError => PARSING_ERROR,
+        ErrorType::Exit(status) => *status,
+        _ => RUNTIME_ERROR,
+    }
+}
+
+fn find_command(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+fn print_usage() {
+    println!("Usage:\n");
+    println!("func [options] [command] [args...]\n");
+    println!("commands:");
+    for command in COMMANDS {
+        println!(
+            "{:<width$}:   {}",
+            command.usage,
+            command.description,
+            width = HELP_COLUMN
+        );
+    }
+    println!(
+        "{:<width$}:   same as `func run <file> [args...]`.",
+        "func <file> [args...]",
+        width = HELP_COLUMN
+    );
+    println!(
+        "{:<width$}:   prints help for a command.",
+        "func help <command>",
+        width = HELP_COLUMN
+    );
+    println!("\noptions:");
+    print_option("-h, --help", "prints help.");
+    print_option("-V, --version", "prints the version.");
+    print_option(
+        "--error-format=<human|json>",
+        "prints errors as human readable text (default)\nor as one JSON object per line.",
+    );
+}
+
+fn print_command_usage(command: &Command) {
+    println!("Usage:\n\n{}\n\n{}", command.usage, command.description);
+    if !command.options.is_empty() {
+        println!("\noptions:");
+        for (name, description) in command.options {
+            print_option(name, description);
+        }
+    }
+}
+
+fn print_option(name: &str, description: &str) {
+    let mut lines = description.lines();
+    println!(
+        "{:<width$}:   {}",
+        name,
+        lines.next().unwrap_or_default(),
+        width = HELP_COLUMN
+    );
+    for line in lines {
+        println!("{:<width$}    {}", "", line, width = HELP_COLUMN);
+    }
+}
+
+pub fn usage_error(message: &str) -> i32 {
+    eprintln!("Error: {}.", message);
+    eprintln!("Run `func --help` for usage.");
+    USAGE
+}
+
+pub fn io_error(path: &str, err: std::io::Error) -> Error {
+    Error::new(
+        ErrorType::IoError,
+        code::IO_FAILURE,
+        format!("Could not access `{}`: {}", path, err),
+        Position::new(path.to_string(), 0, 0, 0),
+    )
+}
+
+// `--name` or `--name=value`, `default` being the value of the first.
+pub fn flag<'a>(arg: &'a str, name: &str, default: &'a str) -> Option<&'a str> {
+    match arg.strip_prefix(name) {
+        Some("") => Some(default),
+        Some(value) => value.strip_prefix('='),
+        None => None,
+    }
+}
+
+fn parse_limit<T: FromStr>(name: &str, value: &str) -> Result<T, i32> {
+    value
+        .parse()
+        .map_err(|_| usage_error(&format!("`{}` expects a whole number", name)))
+}
+
+pub fn write_coverage(coverage: &Coverage, path: &str) -> Result<(), Error> {
+    eprint!("{}", coverage.summary());
+    write(path, coverage.lcov()).map_err(|err| io_error(path, err))?;
+    eprintln!("\nWrote LCOV coverage to {}", path);
+    Ok(())
+}
+
+pub fn read_source(source_path: &str) -> Result<String, Error> {
+    read_to_string(source_path).map_err(|err| io_error(source_path, err))
+}
+
+pub fn lex(source_path: &str, source: &str) -> Result<Vec<Token>, Error> {
+    Lexer::new(source_path.to_string(), source).lex()
+}
+
+pub fn parse(source_path: &str, source: &str) -> Result<Program, Error> {
+    Parser::new(lex(source_path, source)?).parse()
+}
+
+fn single_file<'a>(args: &'a [String], command: &str) -> Result<&'a str, i32> {
+    match args {
+        [source_path] => Ok(source_path),
+        [] => Err(usage_error(&format!("`{}` expects a file", command))),
+        _ => Err(usage_error(&format!("`{}` expects a single file", command))),
+    }
+}
+
+fn run_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    let mut args = args;
+    let mut profile = None;
+    let mut coverage = None;
+    let mut trace = None;
+    let mut limits = Limits::default();
+    let mut capabilities = Capabilities::default();
+    while let Some(arg) = args.first() {
+        if let Some(path) = flag(arg, "--profile", "profile.folded") {
+            profile = Some(path);
+        } else if let Some(path) = flag(arg, "--coverage", "lcov.info") {
+            coverage = Some(path);
+        } else if let Some(functions) = flag(arg, "--trace", "") {
+            trace = Some(functions);
+        } else if let Some(steps) = flag(arg, "--max-steps", "") {
+            limits.steps = Some(match parse_limit("--max-steps", steps) {
+                Ok(steps) => steps,
+                Err(status) => return Ok(status),
+            });
+        } else if let Some(depth) = flag(arg, "--max-depth", "") {
+            limits.depth = Some(match parse_limit("--max-depth", depth) {
+                Ok(depth) => depth,
+                Err(status) => return Ok(status),
+            });
+        } else if let Some(size) = flag(arg, "--max-size", "") {
+            limits.size = Some(match parse_limit("--max-size", size) {
+                Ok(size) => size,
+                Err(status) => return Ok(status),
+            });
+        } else if let Some(time) = flag(arg, "--timeout", "") {
+            limits.time = Some(match parse_limit("--timeout", time) {
+                Ok(time) => Duration::from_millis(time),
+                Err(status) => return Ok(status),
+            });
+        } else if arg == "--sandbox" {
+            capabilities = Capabilities::none();
+        } else {
+            break;
+        }
+        args = &args[1..];
+    }
+
+    let (source_path, source, script_args) = match args {
+        [flag, source, script_args @ ..] if flag == "-e" => {
+            ("<inline>", source.clone(), script_args)
+        }
+        [flag] if flag == "-e" => return Ok(usage_error("`-e` expects code")),
+        [path, script_args @ ..] if path == "-" => {
+            let mut source = String::new();
+            stdin()
+                .read_to_string(&mut source)
+                .map_err(|err| io_error("<stdin>", err))?;
+            ("<stdin>", source, script_args)
+        }
+        [path, script_args @ ..] => (path.as_str(), read_source(path)?, script_args),
+        [] => return Ok(usage_error("`run` expects a file")),
+    };
+
+    let program = parse(source_path, &source)?;
+    let mut interpreter = Interpreter::new()
+        .with_args(script_args.to_vec())
+        .with_capabilities(capabilities)
+        .with_limits(limits);
+    let profiler = profile.map(|_| Rc::new(RefCell::new(Profiler::new())));
+    if let Some(profiler) = &profiler {
+        interpreter = interpreter.with_hook(profiler.clone());
+    }
+    let recorder = coverage.map(|_| {
+        let mut recorder = Coverage::new();
+        recorder.add_program(&program);
+        Rc::new(RefCell::new(recorder))
+    });
+    if let Some(recorder) = &recorder {
+        interpreter = interpreter.with_hook(recorder.clone());
+    }
+    if let Some(functions) = trace {
+        let functions = functions
+            .split(',')
+            .filter(|function| !function.is_empty())
+            .map(str::to_string)
+            .collect();
+        interpreter = interpreter.with_hook(
+            Tracer::new(stderr())
+                .with_functions(functions)
+                .with_source(source_path, &source),
+        );
+    }
+    let result = interpreter.interpret(program);
+
+    if let (Some(path), Some(profiler)) = (profile, profiler) {
+        let mut profiler = profiler.borrow_mut();
+        profiler.finish();
+        eprint!("{}", profiler.summary(PROFILE_LINES));
+        write(path, profiler.folded()).map_err(|err| io_error(path, err))?;
+        eprintln!("\nWrote folded stacks to {}", path);
+    }
+    if let (Some(path), Some(recorder)) = (coverage, recorder) {
+        write_coverage(&recorder.borrow(), path)?;
+    }
+    result?;
+
+    Ok(SUCCESS)
+}
+
+fn repl_command(args: &[String], options: &Options) -> Result<i32, Error> {
+    if !args.is_empty() {
+        return Ok(usage_error("`repl` doesn't take arguments"));
+    }
+
+    repl::run_repl(options.error_format);
+    Ok(SUCCESS)
+}
+
+fn check_command(args: &[String], options: &Options) -> Result<i32, Error> {
+    if args.is_empty() {
+        return Ok(usage_error("`check` expects at least one file"));
+    }
+
+    let mut status = SUCCESS;
+    for source_path in args {
+        let errors = match parse(source_path, &read_source(source_path)?) {
+            Ok(program) => Resolver::new().resolve(&program),
+            Err(err) => vec![err],
+        };
+        for err in errors {
+            options.error_format.report(&err);
+            status = FAILURE;
+        }
+    }
+
+    Ok(status)
+}
+
+fn tokens_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    let source_path = match single_file(args, "tokens") {
+        Ok(source_path) => source_path,
+        Err(status) => return Ok(status),
+    };
+
+    print_tokens(&lex(source_path, &read_source(source_path)?)?);
+
+    Ok(SUCCESS)
+}
+
+pub fn print_tokens(tokens: &[Token]) {
+    for token in tokens {
+        println!(
+            "{:>4}:{:<4} {:<14} {}",
+            token.position.row,
+            token.position.column,
+            format!("{:?}", token.ttype),
+            token.lexeme
+        );
+    }
+}
+
+fn ast_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    let (cst, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--cst" => (true, rest),
+        _ => (false, args),
+    };
+    let source_path = match single_file(args, "ast") {
+        Ok(source_path) => source_path,
+        Err(status) => return Ok(status),
+    };
+
+    let source = read_source(source_path)?;
+    if cst {
+        print!("{}", SyntaxNode::parse(source_path, &source)?.dump());
+    } else {
+        println!("{:#?}", parse(source_path, &source)?);
+    }
+
+    Ok(SUCCESS)
+}
+
+fn fmt_command(args: &[String], _options: &Options) -> Result<i32, Error> {
+    let (check, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--check" => (true, rest),
+        _ => (false, args),
+    };
+    if args.is_empty() {
+        return Ok(usage_error("`fmt` expects at least one file"));
+    }
+
+    let mut status = SUCCESS;
+    for source_path in args {
+        let source = read_source(source_path)?;
+        // Only well formed files are formatted, the comments are lexed separately.
+        parse(source_path, &source)?;
+        let tokens = Lexer::new(source_path.to_string(), &source)
+            .with_comments()
+            .lex()?;
+        let formatted = Formatter::new(tokens).format();
+
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{} is not formatted", source_path);
+            status = FAILURE;
+        } else {
+            write(source_path, formatted).map_err(|err| io_error(source_path, err))?;
+        }
+    }
+
+    Ok(status)
+}
@@ -0,0 +1,36 @@
+// The `Content-Length` framing shared by the language server and the debug adapter.
+
+use std::io::{self, stdout, BufRead, Write};
+
+use func::common::json::Value;
+
+// Reads one message, `None` at the end of the input and `Value::Null` if the body isn't JSON.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    Ok(Some(
+        Value::parse(&String::from_utf8_lossy(&body)).unwrap_or(Value::Null),
+    ))
+}
+
+pub fn write_message(message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    let mut output = stdout();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
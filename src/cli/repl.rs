@@ -0,0 +1,399 @@
+use std::{env, fs::write, path::PathBuf, process::exit, time::Instant};
+
+use func::{
+    common::{
+        ast::Statement,
+        error::{code, Error, ErrorType},
+        object::Object,
+        token::TokenType,
+    },
+    frontend::lexer::Lexer,
+    runtime::{interpreter::Interpreter, stdlib},
+};
+
+use super::{
+    editor::{Completer, Editor, ReadResult},
+    io_error, lex, parse, print_tokens, read_source,
+    terminal::interrupt_on_ctrl_c,
+    ErrorFormat, KEYWORD_SIGNATURES,
+};
+
+const PROMPT: &str = ":> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+
+const COMMANDS: &[(&str, &str)] = &[
+    (":vars", "lists variables and their values."),
+    (":funcs", "lists functions."),
+    (":type <expr>", "prints the type of an expression."),
+    (":ast <expr>", "prints the syntax tree of an expression."),
+    (":tokens <expr>", "prints the tokens of an expression."),
+    (
+        ":time <expr>",
+        "evaluates an expression and prints how long it took.",
+    ),
+    (":load <file>", "runs a file in this session."),
+    (
+        ":save <file>",
+        "writes every input accepted so far to a file.",
+    ),
+    (":reset", "forgets all variables, functions and inputs."),
+    (":help", "prints this message."),
+    (":quit", "exits, same as [Ctrl] + [d]."),
+];
+
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".func_history"))
+}
+
+// Input is incomplete while a string or a bracket is left open, the REPL then keeps reading.
+fn is_incomplete(source: &str) -> bool {
+    match lex("stdin", source) {
+        Ok(tokens) => {
+            let mut depth: i64 = 0;
+            for token in tokens {
+                match token.ttype {
+                    TokenType::OpenParen | TokenType::OpenBrack | TokenType::OpenCurly => {
+                        depth += 1
+                    }
+                    TokenType::CloseParen | TokenType::CloseBrack | TokenType::CloseCurly => {
+                        depth -= 1
+                    }
+                    _ => {}
+                }
+            }
+            depth > 0
+        }
+        Err(err) => err.code() == code::UNTERMINATED_STRING,
+    }
+}
+
+fn last_word(line: &str) -> &str {
+    line.rsplit(|character: char| !(character.is_alphanumeric() || character == '_'))
+        .next()
+        .unwrap_or_default()
+}
+
+fn echo(value: &Object) {
+    match value {
+        Object::Nil(..) => {}
+        Object::String(string, ..) => println!("\"{}\"", string),
+        value => println!("{}", value),
+    }
+}
+
+struct Repl {
+    interpreter: Interpreter,
+    // Inputs that ran without an error, in order, for `:save`.
+    session: Vec<String>,
+    error_format: ErrorFormat,
+}
+
+impl Repl {
+    fn evaluate(&mut self, source: &str) -> Result<Object, Error> {
+        let program = parse("stdin", source)?;
+        let is_expression = matches!(program.last(), Some(Statement::Expression(..)));
+        let value = self.interpreter.evaluate(program)?;
+        self.session.push(source.trim_end().to_string());
+        if is_expression {
+            Ok(value)
+        } else {
+            Ok(Object::Nil(Default::default()))
+        }
+    }
+
+    fn command(&mut self, line: &str) -> Result<(), Error> {
+        let (command, argument) = match line.find(char::is_whitespace) {
+            Some(index) => (&line[..index], line[index..].trim()),
+            None => (line, ""),
+        };
+
+        match command {
+            ":vars" => {
+                for (name, value) in self.interpreter.variables() {
+                    println!("{} = {}", name, value);
+                }
+            }
+            ":funcs" => {
+                for function in self.interpreter.functions() {
+                    let paramiters: Vec<&str> = function
+                        .paramiters
+                        .iter()
+                        .map(|paramiter| paramiter.lexeme.as_str())
+                        .collect();
+                    println!(
+                        "func {}({})",
+                        function.identifier.lexeme,
+                        paramiters.join(", ")
+                    );
+                }
+            }
+            ":type" => println!("{}", self.evaluate(argument)?.type_name()),
+            ":ast" => println!("{:#?}", parse("stdin", argument)?),
+            ":tokens" => print_tokens(&lex("stdin", argument)?),
+            ":time" => {
+                let start = Instant::now();
+                let value = self.evaluate(argument)?;
+                let elapsed = start.elapsed();
+                echo(&value);
+                println!("took {:?}", elapsed);
+            }
+            ":load" => {
+                let source = read_source(argument)?;
+                let program = parse(argument, &source)?;
+                self.interpreter.interpret(program)?;
+                self.session.push(source.trim_end().to_string());
+            }
+            ":save" => {
+                let mut source = self.session.join("\n");
+                source.push('\n');
+                write(argument, source).map_err(|err| io_error(argument, err))?;
+                println!("saved {} inputs to {}", self.session.len(), argument);
+            }
+            ":reset" => {
+                self.interpreter = Interpreter::new();
+                interrupt_on_ctrl_c(self.interpreter.interrupt_handle());
+                self.session.clear();
+            }
+            ":help" => {
+                for (usage, description) in COMMANDS {
+                    println!("{:<16}{}", usage, description);
+                }
+            }
+            ":quit" => exit(0),
+            command => println!("Unknown command `{}`, `:help` lists the commands.", command),
+        }
+
+        Ok(())
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Lexer::keywords().map(str::to_string).collect();
+        names.extend(
+            self.interpreter
+                .variables()
+                .into_iter()
+                .map(|(name, _)| name.to_string()),
+        );
+        names.extend(
+            self.interpreter
+                .functions()
+                .into_iter()
+                .map(|function| function.identifier.lexeme.clone()),
+        );
+        names.extend(stdlib::builtins().map(|builtin| builtin.name.to_string()));
+        names.extend(stdlib::constants().map(|(name, _)| name.to_string()));
+        names
+    }
+
+    fn report(&self, err: Error) {
+        if let ErrorType::Exit(status) = err.kind() {
+            exit(*status);
+        }
+        self.error_format.report(&err);
+    }
+}
+
+impl Completer for Repl {
+    fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let word = if line.starts_with(':') && !line.contains(char::is_whitespace) {
+            line
+        } else {
+            last_word(line)
+        };
+        let start = line.chars().count() - word.chars().count();
+        if word.is_empty() {
+            return (start, Vec::new());
+        }
+
+        let mut candidates: Vec<String> = if word.starts_with(':') {
+            COMMANDS
+                .iter()
+                .map(|(usage, _)| usage.split(' ').next().unwrap_or(usage).to_string())
+                .collect()
+        } else {
+            self.names()
+        };
+        candidates.retain(|candidate| candidate.starts_with(word));
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+
+    // Finds the innermost call left open before the cursor and describes its parameters.
+    fn hint(&self, line: &str) -> Option<String> {
+        let mut open_parens = Vec::new();
+        let mut in_string = false;
+        for (index, character) in line.char_indices() {
+            match character {
+                '"' => in_string = !in_string,
+                '(' if !in_string => open_parens.push(index),
+                ')' if !in_string => {
+                    open_parens.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let name = last_word(&line[..*open_parens.last()?]);
+
+        if let Some(function) = self
+            .interpreter
+            .functions()
+            .into_iter()
+            .find(|function| function.identifier.lexeme == name)
+        {
+            let paramiters: Vec<&str> = function
+                .paramiters
+                .iter()
+                .map(|paramiter| paramiter.lexeme.as_str())
+                .collect();
+            return Some(format!("{}({})", name, paramiters.join(", ")));
+        }
+        if let Some((_, hint)) = KEYWORD_SIGNATURES
+            .iter()
+            .find(|(keyword, _)| *keyword == name)
+        {
+            return Some(hint.to_string());
+        }
+        stdlib::find(name).map(|builtin| builtin.signature())
+    }
+}
+
+pub fn run_repl(error_format: ErrorFormat) {
+    println!("Press [Ctrl] + [d] to exit, [Ctrl] + [c] to stop an evaluation, `:help` lists the commands.");
+    let mut editor = Editor::new(history_path());
+    let mut repl = Repl {
+        interpreter: Interpreter::new(),
+        session: Vec::new(),
+        error_format,
+    };
+    // Stops a long evaluation and returns to the prompt.
+    interrupt_on_ctrl_c(repl.interpreter.interrupt_handle());
+    let mut source = String::new();
+
+    loop {
+        let prompt = if source.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        let line = match editor.read_line(prompt, &repl) {
+            Ok(ReadResult::Line(line)) => line,
+            Ok(ReadResult::Interrupted) => {
+                source.clear();
+                continue;
+            }
+            Ok(ReadResult::Eof) | Err(..) => break,
+        };
+        editor.add_history(&line);
+
+        if source.is_empty() && line.trim_start().starts_with(':') {
+            if let Err(err) = repl.command(line.trim()) {
+                repl.report(err);
+            }
+            continue;
+        }
+
+        source.push_str(&line);
+        source.push('\n');
+        if is_incomplete(&source) {
+            continue;
+        }
+
+        match repl.evaluate(&source) {
+            Ok(value) => echo(&value),
+            Err(err) => repl.report(err),
+        }
+        source.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repl(source: &str) -> Repl {
+        let mut repl = Repl {
+            interpreter: Interpreter::with_io(std::io::empty(), Vec::new()),
+            session: Vec::new(),
+            error_format: ErrorFormat::Human,
+        };
+        repl.evaluate(source).unwrap();
+        repl
+    }
+
+    #[test]
+    fn open_brackets_and_strings_are_incomplete() {
+        assert!(is_incomplete("func add(x, y) {"));
+        assert!(is_incomplete("let xs = [1, 2,"));
+        assert!(is_incomplete("write(\"hello"));
+        assert!(!is_incomplete("func add(x, y) { x + y }"));
+        assert!(!is_incomplete("let x = 1"));
+        // Errors other than an open string are reported instead of waiting for more input.
+        assert!(!is_incomplete("let x = $"));
+        assert!(!is_incomplete("}"));
+    }
+
+    #[test]
+    fn last_word_stops_at_punctuation() {
+        assert_eq!(last_word("write(total_co"), "total_co");
+        assert_eq!(last_word("let x = y + z1"), "z1");
+        assert_eq!(last_word("write("), "");
+        assert_eq!(last_word(""), "");
+    }
+
+    #[test]
+    fn completion_lists_matching_names() {
+        let repl = repl("let total = 1\nfunc tally(xs) { xs }");
+        let (start, candidates) = repl.complete("write(ta");
+        assert_eq!(start, 6);
+        assert!(candidates.contains(&"tally".to_string()));
+        assert!(candidates.contains(&"tan".to_string()));
+        assert!(!candidates.contains(&"total".to_string()));
+
+        assert_eq!(
+            repl.complete("to").1,
+            vec!["to_number", "to_string", "total"]
+        );
+        assert_eq!(repl.complete(":lo"), (0, vec![":load".to_string()]));
+        assert_eq!(repl.complete("write("), (6, Vec::new()));
+    }
+
+    #[test]
+    fn failed_blocks_leave_no_bindings() {
+        let mut repl = repl(
+            "func handler(message) { 0 }\nfunc fail() {\n    { let inner = 1\n inner + \"x\" }\n}",
+        );
+        assert!(repl.evaluate("{ let outer = 1\n outer + \"x\" }").is_err());
+        repl.evaluate("try(fail, handler)").unwrap();
+        assert!(repl
+            .evaluate("{ let caught = try(fail, handler)\n caught + \"x\" }")
+            .is_err());
+        let names: Vec<&str> = repl
+            .interpreter
+            .variables()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["args"]);
+    }
+
+    #[test]
+    fn hint_describes_the_innermost_open_call() {
+        let repl = repl("func add(x, y) { x + y }");
+        assert_eq!(repl.hint("add(1, "), Some("add(x, y)".to_string()));
+        assert_eq!(
+            repl.hint("write(add(1, 2), sqrt("),
+            Some("sqrt(x)".to_string())
+        );
+        assert_eq!(
+            repl.hint("write(add(1, 2), "),
+            Some("write(value, ...)".to_string())
+        );
+        assert_eq!(
+            repl.hint("write(\"(\", "),
+            Some("write(value, ...)".to_string())
+        );
+        assert_eq!(repl.hint("add(1, 2)"), None);
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use func::runtime::limits::InterruptHandle;
+
+#[cfg(unix)]
+extern "C" {
+    fn isatty(fd: i32) -> i32;
+    fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+}
+
+#[cfg(unix)]
+pub fn is_terminal(fd: i32) -> bool {
+    unsafe { isatty(fd) == 1 }
+}
+
+#[cfg(not(unix))]
+pub fn is_terminal(_fd: i32) -> bool {
+    true
+}
+
+pub const STDIN: i32 = 0;
+
+#[cfg(unix)]
+const SIGINT: i32 = 2;
+
+// The handle [Ctrl] + [c] interrupts. Handles are leaked since the handler may still read
+// the previous one while it's replaced.
+static INTERRUPT: AtomicPtr<InterruptHandle> = AtomicPtr::new(ptr::null_mut());
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_signum: i32) {
+    if let Some(handle) = unsafe { INTERRUPT.load(Ordering::SeqCst).as_ref() } {
+        handle.interrupt();
+    }
+}
+
+// Makes [Ctrl] + [c] interrupt `handle` instead of killing the process.
+pub fn interrupt_on_ctrl_c(handle: InterruptHandle) {
+    let handle = Box::into_raw(Box::new(handle));
+    if INTERRUPT.swap(handle, Ordering::SeqCst).is_null() {
+        #[cfg(unix)]
+        unsafe {
+            signal(SIGINT, on_sigint);
+        }
+    }
+}
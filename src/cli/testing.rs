@@ -0,0 +1,117 @@
+use std::{
+    cell::RefCell,
+    fs::read_dir,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use func::{
+    common::{ast::Statement, error::Error},
+    runtime::{coverage::Coverage, interpreter::Interpreter},
+};
+
+use super::{
+    flag, io_error, parse, read_source, usage_error, write_coverage, Options, FAILURE, SUCCESS,
+};
+
+fn collect_sources(path: &Path, sources: &mut Vec<PathBuf>) -> Result<(), Error> {
+    if path.is_dir() {
+        let entries = read_dir(path).map_err(|err| io_error(&path.display().to_string(), err))?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.starts_with('.') || name == "target" {
+                continue;
+            }
+            if path.is_dir() || name.ends_with(".func") {
+                collect_sources(&path, sources)?;
+            }
+        }
+    } else {
+        sources.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+pub fn test_command(args: &[String], options: &Options) -> Result<i32, Error> {
+    let (coverage, args) = match args.split_first() {
+        Some((arg, rest)) => match flag(arg, "--coverage", "lcov.info") {
+            Some(path) => (Some(path), rest),
+            None => (None, args),
+        },
+        None => (None, args),
+    };
+    let path = match args {
+        [] => ".",
+        [path] => path,
+        _ => return Ok(usage_error("`test` expects at most one path")),
+    };
+    let recorder = Rc::new(RefCell::new(Coverage::new()));
+
+    let mut sources = Vec::new();
+    collect_sources(Path::new(path), &mut sources)?;
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for source_path in sources {
+        let source_path = source_path.display().to_string();
+
+        // Files without tests are plain scripts, so they must not be executed.
+        let results = parse(&source_path, &read_source(&source_path)?).and_then(|program| {
+            if program
+                .iter()
+                .any(|statement| matches!(statement, Statement::Test(..)))
+            {
+                let mut interpreter = Interpreter::new();
+                if coverage.is_some() {
+                    recorder.borrow_mut().add_program(&program);
+                    interpreter = interpreter.with_hook(recorder.clone());
+                }
+                interpreter.run_tests(program)
+            } else {
+                Ok(Vec::new())
+            }
+        });
+
+        match results {
+            Ok(results) => {
+                if results.is_empty() {
+                    continue;
+                }
+                println!("\nrunning {} tests in {}", results.len(), source_path);
+                for result in results {
+                    if let Some(err) = result.error {
+                        println!("test {} ... FAILED", result.name);
+                        options.error_format.report(&err);
+                        failed += 1;
+                    } else {
+                        println!("test {} ... ok", result.name);
+                        passed += 1;
+                    }
+                }
+            }
+            Err(err) => {
+                println!("\ncould not run tests in {}", source_path);
+                options.error_format.report(&err);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "\ntest result: {}. {} passed; {} failed",
+        if failed == 0 { "ok" } else { "FAILED" },
+        passed,
+        failed
+    );
+
+    if let Some(path) = coverage {
+        eprintln!();
+        write_coverage(&recorder.borrow(), path)?;
+    }
+
+    Ok(if failed == 0 { SUCCESS } else { FAILURE })
+}
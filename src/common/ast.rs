@@ -1,4 +1,4 @@
-use super::token::Token;
+use super::{position::Position, token::Token};
 
 pub type Program = Vec<Statement>;
 
@@ -7,17 +7,42 @@ pub enum Statement {
     Let(LetStatement),
     Assignment(AssignmentStatement),
     Function(FunctionStatement),
+    Test(TestStatement),
     BuiltinFunction(BuiltinFunctionStatement),
     Return(Expression),
     Expression(Expression),
 }
 
+impl Statement {
+    // Where the statement starts, as far as its tokens tell. An empty block has no tokens.
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            Statement::Let(let_statement) => Some(&let_statement.identifier.position),
+            Statement::Assignment(assignment_statement) => {
+                Some(&assignment_statement.identifier.position)
+            }
+            Statement::Function(function_statement) => {
+                Some(&function_statement.identifier.position)
+            }
+            Statement::Test(test_statement) => Some(&test_statement.name.position),
+            Statement::BuiltinFunction(builtin_function_statement) => {
+                Some(&builtin_function_statement.keyword.position)
+            }
+            Statement::Return(expression) | Statement::Expression(expression) => {
+                expression.position()
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum BuiltinFunction {
     Read,
     Write,
     Push,
     Pop,
+    Assert,
+    AssertEq,
 }
 
 #[derive(Debug, Clone)]
@@ -114,15 +139,33 @@ impl FunctionStatement {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct TestStatement {
+    pub name: Token,
+    pub block: BlockExpression,
+}
+
+impl TestStatement {
+    pub fn new(name: Token, block: BlockExpression) -> Self {
+        Self { name, block }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BuiltinFunctionStatement {
+    pub keyword: Token,
     pub builtin_function: BuiltinFunction,
     pub arguments: Vec<Expression>,
 }
 
 impl BuiltinFunctionStatement {
-    pub fn new(builtin_function: BuiltinFunction, arguments: Vec<Expression>) -> Self {
+    pub fn new(
+        keyword: Token,
+        builtin_function: BuiltinFunction,
+        arguments: Vec<Expression>,
+    ) -> Self {
         Self {
+            keyword,
             builtin_function,
             arguments,
         }
@@ -142,6 +185,30 @@ pub enum Expression {
     Array(ArrayExpression),
 }
 
+impl Expression {
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            Expression::Block(block_expression) => block_expression
+                .statements
+                .first()
+                .and_then(Statement::position),
+            Expression::If(if_expression) => if_expression.condition.position(),
+            Expression::Binary(binary_expression) => binary_expression.left.position(),
+            Expression::Unary(unary_expression) => Some(&unary_expression.operator.position),
+            Expression::Group(group_expression) => group_expression.child.position(),
+            Expression::Call(call_expression) => Some(&call_expression.identifier.position),
+            Expression::Identifier(identifier_expression) => {
+                Some(&identifier_expression.identifier.position)
+            }
+            Expression::Literal(literal_expression) => Some(&literal_expression.object.position),
+            Expression::Array(array_expression) => array_expression
+                .objects
+                .first()
+                .map(|object| &object.position),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BinaryExpression {
     pub left: Box<Expression>,
@@ -1,11 +1,52 @@
 use std::fmt::{Display, Formatter, Result};
 
-use super::position::Position;
+use super::{json, position::Position};
 
+pub mod code {
+    // Lexing errors.
+    pub const UNEXPECTED_CHARACTER: &str = "E0101";
+    pub const UNTERMINATED_STRING: &str = "E0102";
+    pub const INVALID_NUMBER: &str = "E0103";
+
+    // Parsing errors.
+    pub const EXPECTED_TOKEN: &str = "E0201";
+    pub const UNEXPECTED_TOKEN: &str = "E0202";
+
+    // Runtime errors.
+    pub const UNDEFINED_VARIABLE: &str = "E0301";
+    pub const UNDEFINED_FUNCTION: &str = "E0302";
+    pub const ARGUMENT_COUNT: &str = "E0303";
+    pub const UNSUPPORTED_OPERAND: &str = "E0304";
+    pub const OPERAND_MISMATCH: &str = "E0305";
+    pub const INVALID_OPERATOR: &str = "E0306";
+    pub const NOT_AN_ARRAY: &str = "E0307";
+    pub const IO_FAILURE: &str = "E0308";
+    pub const ASSERTION_FAILED: &str = "E0309";
+    pub const ARGUMENT_TYPE: &str = "E0310";
+    pub const EXIT: &str = "E0311";
+    pub const STEP_LIMIT: &str = "E0312";
+    pub const DEPTH_LIMIT: &str = "E0313";
+    pub const SIZE_LIMIT: &str = "E0314";
+    pub const TIME_LIMIT: &str = "E0315";
+    pub const NOT_ALLOWED: &str = "E0316";
+    pub const INTERRUPTED: &str = "E0317";
+    pub const INDEX_OUT_OF_RANGE: &str = "E0318";
+    pub const NOT_A_FUNCTION: &str = "E0319";
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ErrorType {
     LexingError,
     ParsingError,
     RuntimeError,
+    TypeError,
+    NameError,
+    ArityError,
+    IndexError,
+    IoError,
+    AssertionError,
+    // Raised by `exit` to unwind the program, it isn't a failure by itself.
+    Exit(i32),
 }
 
 impl Display for ErrorType {
@@ -14,29 +55,87 @@ impl Display for ErrorType {
             Self::LexingError => write!(f, "LexingError"),
             Self::ParsingError => write!(f, "ParsingError"),
             Self::RuntimeError => write!(f, "RuntimeError"),
+            Self::TypeError => write!(f, "TypeError"),
+            Self::NameError => write!(f, "NameError"),
+            Self::ArityError => write!(f, "ArityError"),
+            Self::IndexError => write!(f, "IndexError"),
+            Self::IoError => write!(f, "IoError"),
+            Self::AssertionError => write!(f, "AssertionError"),
+            Self::Exit(..) => write!(f, "Exit"),
         }
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct Error {
     e_type: ErrorType,
+    code: &'static str,
     message: String,
     position: Position,
 }
 
 impl Error {
-    pub fn new(e_type: ErrorType, message: String, position: Position) -> Self {
+    pub fn new(e_type: ErrorType, code: &'static str, message: String, position: Position) -> Self {
         Self {
             e_type,
+            code,
             message,
             position,
         }
     }
 
+    pub fn kind(&self) -> &ErrorType {
+        &self.e_type
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
     pub fn report(&self) {
-        eprintln!(
-            "{}: {} in line {} (file: {}).",
-            self.e_type, self.message, self.position.row, self.position.source_path
-        );
+        eprintln!("{}", self);
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"level\":\"error\",\"kind\":{},\"code\":{},\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"span\":{{\"start\":{},\"end\":{}}}}}",
+            json::escape(&self.e_type.to_string()),
+            json::escape(self.code),
+            json::escape(&self.message),
+            json::escape(&self.position.source_path),
+            self.position.row,
+            self.position.column,
+            self.position.column,
+            self.position.column + self.position.length,
+        )
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // Errors about a whole file, like failing to read it, have no line.
+        if self.position.row == 0 {
+            write!(
+                f,
+                "{}[{}]: {} (file: {}).",
+                self.e_type, self.code, self.message, self.position.source_path
+            )
+        } else {
+            write!(
+                f,
+                "{}[{}]: {} in line {} (file: {}).",
+                self.e_type, self.code, self.message, self.position.row, self.position.source_path
+            )
+        }
     }
 }
+
+impl std::error::Error for Error {}
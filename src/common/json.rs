@@ -0,0 +1,304 @@
+use std::fmt::{Display, Formatter, Result};
+
+pub fn escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len() + 2);
+    escaped.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// A parsed JSON document. Objects keep their keys in order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn parse(text: &str) -> Option<Value> {
+        let mut parser = JsonParser {
+            chars: text.chars().collect(),
+            current: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.current == parser.chars.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    pub fn object(entries: Vec<(&str, Value)>) -> Value {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    // Follows a path of object keys, like `params.textDocument.uri`.
+    pub fn pointer(&self, path: &str) -> Option<&Value> {
+        path.split('.').try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(boolean) => write!(f, "{}", boolean),
+            Value::Number(number) if number.fract() == 0.0 && number.abs() < 1e15 => {
+                write!(f, "{}", *number as i64)
+            }
+            Value::Number(number) if number.is_finite() => write!(f, "{}", number),
+            Value::Number(..) => write!(f, "null"),
+            Value::String(string) => write!(f, "{}", escape(string)),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(entries) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", escape(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        Value::Bool(boolean)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(number: usize) -> Self {
+        Value::Number(number as f64)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(number: i32) -> Self {
+        Value::Number(number as f64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::String(string.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::String(string)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Self {
+        Value::Array(values)
+    }
+}
+
+struct JsonParser {
+    chars: Vec<char>,
+    current: usize,
+}
+
+impl JsonParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.current += 1;
+        Some(character)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.current += 1;
+        }
+    }
+
+    fn eat_word(&mut self, word: &str, value: Value) -> Option<Value> {
+        for expected in word.chars() {
+            if self.next()? != expected {
+                return None;
+            }
+        }
+        Some(value)
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match self.peek()? {
+            'n' => self.eat_word("null", Value::Null),
+            't' => self.eat_word("true", Value::Bool(true)),
+            'f' => self.eat_word("false", Value::Bool(false)),
+            '"' => self.string().map(Value::String),
+            '[' => self.array(),
+            '{' => self.object(),
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let start = self.current;
+        while matches!(self.peek(), Some('0'..='9' | '-' | '+' | '.' | 'e' | 'E')) {
+            self.current += 1;
+        }
+        let number: String = self.chars[start..self.current].iter().collect();
+        number.parse().ok().map(Value::Number)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.next();
+        let mut string = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(string),
+                '\\' => match self.next()? {
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    'b' => string.push('\u{8}'),
+                    'f' => string.push('\u{c}'),
+                    'u' => {
+                        let mut code = self.hex()?;
+                        // Characters outside the basic plane come as a surrogate pair.
+                        if (0xd800..0xdc00).contains(&code) {
+                            if self.next()? != '\\' || self.next()? != 'u' {
+                                return None;
+                            }
+                            let low = self.hex()?;
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low.checked_sub(0xdc00)?);
+                        }
+                        string.push(char::from_u32(code)?);
+                    }
+                    character => string.push(character),
+                },
+                character => string.push(character),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Option<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            code = code * 16 + self.next()?.to_digit(16)?;
+        }
+        Some(code)
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.next();
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == ']' {
+            self.next();
+            return Some(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Some(Value::Array(values)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.next();
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == '}' {
+            self.next();
+            return Some(Value::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek()? != '"' {
+                return None;
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.next()? != ':' {
+                return None;
+            }
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Some(Value::Object(entries)),
+                _ => return None,
+            }
+        }
+    }
+}
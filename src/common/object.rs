@@ -1,21 +1,30 @@
 use std::fmt;
 
 use super::{
-    error::{Error, ErrorType},
+    error::{code, Error, ErrorType},
     position::Position,
 };
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone)]
 pub struct Meta {
     pub is_return: bool,
 }
 
+// Meta only describes how a value travelled, so it never makes two values unequal.
+impl PartialEq for Meta {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Object {
     Number(f64, Meta),
     String(String, Meta),
     Boolean(bool, Meta),
     Array(Vec<Object>, Meta),
+    // A user function or builtin referred to by name.
+    Function(String, Meta),
     Nil(Meta),
 }
 
@@ -23,7 +32,7 @@ impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Number(number, ..) => write!(f, "{}", number),
-            Self::String(string, ..) => write!(f, "{}", string.replace("\\n", "\n")),
+            Self::String(string, ..) => write!(f, "{}", string),
             Self::Boolean(boolean, ..) => write!(f, "{}", boolean),
             Self::Array(array, ..) => {
                 write!(f, "[")?;
@@ -33,18 +42,31 @@ impl fmt::Display for Object {
                 write!(f, "]")?;
                 Ok(())
             }
+            Self::Function(name, ..) => write!(f, "<func {}>", name),
             Self::Nil(..) => write!(f, "nil"),
         }
     }
 }
 
 impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Number(..) => "number",
+            Self::String(..) => "string",
+            Self::Boolean(..) => "boolean",
+            Self::Array(..) => "array",
+            Self::Function(..) => "function",
+            Self::Nil(..) => "nil",
+        }
+    }
+
     pub fn meta(&self) -> &Meta {
         match self {
             Self::Number(_, meta) => meta,
             Self::String(_, meta) => meta,
             Self::Boolean(_, meta) => meta,
             Self::Array(_, meta) => meta,
+            Self::Function(_, meta) => meta,
             Self::Nil(meta) => meta,
         }
     }
@@ -55,6 +77,7 @@ impl Object {
             Self::String(_, meta) => meta.is_return = true,
             Self::Boolean(_, meta) => meta.is_return = true,
             Self::Array(_, meta) => meta.is_return = true,
+            Self::Function(_, meta) => meta.is_return = true,
             Self::Nil(meta) => meta.is_return = true,
         }
     }
@@ -65,41 +88,44 @@ impl Object {
             Self::String(_, meta) => meta.is_return,
             Self::Boolean(_, meta) => meta.is_return,
             Self::Array(_, meta) => meta.is_return,
+            Self::Function(_, meta) => meta.is_return,
             Self::Nil(meta) => meta.is_return,
         }
     }
 
     pub fn is_truthy(&self) -> bool {
         match self {
-            Self::Boolean(boolean, ..) => boolean.clone(),
+            Self::Boolean(boolean, ..) => *boolean,
             Self::Nil(..) => false,
             _ => true,
         }
     }
 
-    pub fn push(&mut self, object: Object, position: Position) -> Result<Object, Error> {
+    pub fn push(&mut self, object: Object, position: Position) -> Result<(), Error> {
         match self {
             Object::Array(array, ..) => {
                 array.push(object);
-                Ok(Object::Array(array.clone(), Meta::default()))
+                Ok(())
             }
             _ => Err(Error::new(
-                ErrorType::RuntimeError,
+                ErrorType::TypeError,
+                code::NOT_AN_ARRAY,
                 format!("`{}` does not have `push` method associated with it", self),
                 position,
             )),
         }
     }
 
-    pub fn pop(&mut self, position: Position) -> Result<Object, Error> {
+    pub fn pop(&mut self, position: Position) -> Result<(), Error> {
         match self {
             Object::Array(array, ..) => {
                 array.pop();
-                Ok(Object::Array(array.clone(), Meta::default()))
+                Ok(())
             }
             _ => Err(Error::new(
-                ErrorType::RuntimeError,
-                format!("`{}` does not have `push` method associated with it", self),
+                ErrorType::TypeError,
+                code::NOT_AN_ARRAY,
+                format!("`{}` does not have `pop` method associated with it", self),
                 position,
             )),
         }
@@ -1,11 +1,18 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Position {
     pub source_path: String,
     pub row: usize,
+    pub column: usize,
+    pub length: usize,
 }
 
 impl Position {
-    pub fn new(source_path: String, row: usize) -> Self {
-        Self { source_path, row }
+    pub fn new(source_path: String, row: usize, column: usize, length: usize) -> Self {
+        Self {
+            source_path,
+            row,
+            column,
+            length,
+        }
     }
 }
@@ -13,6 +13,7 @@ pub enum TokenType {
     Plus,
     Minus,
     Star,
+    StarStar,
     Slash,
     Modulo,
 
@@ -41,12 +42,18 @@ pub enum TokenType {
     If,
     Else,
     Return,
+    Test,
 
     Read,
     Write,
     Push,
     Pop,
+    Assert,
+    AssertEq,
 
+    Whitespace,
+    Newline,
+    Comment,
     EOF,
 }
 
@@ -62,6 +69,7 @@ impl Display for TokenType {
             Self::Plus => write!(f, "+"),
             Self::Minus => write!(f, "-"),
             Self::Star => write!(f, "*"),
+            Self::StarStar => write!(f, "**"),
             Self::Slash => write!(f, "/"),
             Self::Modulo => write!(f, "%"),
 
@@ -90,12 +98,18 @@ impl Display for TokenType {
             Self::If => write!(f, "if"),
             Self::Else => write!(f, "else"),
             Self::Return => write!(f, "return"),
+            Self::Test => write!(f, "test"),
 
             Self::Read => write!(f, "read"),
             Self::Write => write!(f, "write"),
             Self::Push => write!(f, "push"),
             Self::Pop => write!(f, "pop"),
+            Self::Assert => write!(f, "assert"),
+            Self::AssertEq => write!(f, "assert_eq"),
 
+            Self::Whitespace => write!(f, "whitespace"),
+            Self::Newline => write!(f, "newline"),
+            Self::Comment => write!(f, "comment"),
             Self::EOF => write!(f, "\0"),
         }
     }
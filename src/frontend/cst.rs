@@ -0,0 +1,229 @@
+use std::fmt::Write;
+
+use crate::common::{
+    ast::Program,
+    error::Error,
+    token::{Token, TokenType},
+};
+
+use super::{lexer::Lexer, parser::Parser};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyntaxKind {
+    Program,
+
+    LetStatement,
+    AssignmentStatement,
+    FunctionStatement,
+    TestStatement,
+    BuiltinFunctionStatement,
+    ReturnStatement,
+
+    BlockExpression,
+    IfExpression,
+    BinaryExpression,
+    UnaryExpression,
+    GroupExpression,
+    CallExpression,
+    IdentifierExpression,
+    LiteralExpression,
+    ArrayExpression,
+}
+
+// What the parser records while it parses, with the index of the token it happened at.
+#[derive(Debug)]
+pub(crate) enum Event {
+    Start(SyntaxKind, usize),
+    Finish(usize),
+}
+
+// A significant token with the whitespace, newlines and comments around it. Trivia up to the
+// end of the token's line trails it, everything after that leads the next token.
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    pub leading: Vec<Token>,
+    pub token: Token,
+    pub trailing: Vec<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+pub fn is_trivia(ttype: &TokenType) -> bool {
+    matches!(
+        ttype,
+        TokenType::Whitespace | TokenType::Newline | TokenType::Comment
+    )
+}
+
+impl SyntaxToken {
+    fn write_text(&self, text: &mut String) {
+        for trivia in &self.leading {
+            text.push_str(&trivia.lexeme);
+        }
+        text.push_str(&self.token.lexeme);
+        for trivia in &self.trailing {
+            text.push_str(&trivia.lexeme);
+        }
+    }
+}
+
+impl SyntaxNode {
+    // Builds the tree of a whole file. The root is a `Program` node whose last token is EOF,
+    // which carries the trivia at the end of the file.
+    pub fn parse(source_path: &str, source: &str) -> Result<Self, Error> {
+        let tokens = Lexer::new(source_path.to_string(), source)
+            .with_trivia()
+            .lex()?;
+        let tokens = attach_trivia(tokens);
+
+        let mut parser = Parser::new(tokens.iter().map(|token| token.token.clone()).collect());
+        parser.parse()?;
+
+        Ok(build(parser.take_events(), tokens))
+    }
+
+    // The source the tree was built from, byte for byte.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for token in self.tokens() {
+            token.write_text(&mut text);
+        }
+        text
+    }
+
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(..) => None,
+        })
+    }
+
+    // Lowers the tree to the syntax tree the interpreter runs.
+    pub fn to_program(&self) -> Result<Program, Error> {
+        let tokens = self
+            .tokens()
+            .into_iter()
+            .map(|token| token.token.clone())
+            .collect();
+        Parser::new(tokens).parse()
+    }
+
+    // An indented outline of the tree, one node or token per line.
+    pub fn dump(&self) -> String {
+        let mut output = String::new();
+        self.dump_into(&mut output, 0);
+        output
+    }
+
+    fn dump_into(&self, output: &mut String, depth: usize) {
+        writeln!(output, "{}{:?}", "  ".repeat(depth), self.kind).ok();
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.dump_into(output, depth + 1),
+                SyntaxElement::Token(token) => {
+                    writeln!(
+                        output,
+                        "{}{:?} {:?}",
+                        "  ".repeat(depth + 1),
+                        token.token.ttype,
+                        token.token.lexeme
+                    )
+                    .ok();
+                }
+            }
+        }
+    }
+}
+
+fn attach_trivia(tokens: Vec<Token>) -> Vec<SyntaxToken> {
+    let mut syntax_tokens: Vec<SyntaxToken> = Vec::new();
+    let mut leading = Vec::new();
+    // Trivia only trails a token until the first newline after it.
+    let mut trailing = false;
+
+    for token in tokens {
+        if !is_trivia(&token.ttype) {
+            syntax_tokens.push(SyntaxToken {
+                leading: std::mem::take(&mut leading),
+                token,
+                trailing: Vec::new(),
+            });
+            trailing = true;
+            continue;
+        }
+
+        if token.ttype == TokenType::Newline {
+            trailing = false;
+        }
+        match syntax_tokens.last_mut() {
+            Some(last) if trailing => last.trailing.push(token),
+            _ => leading.push(token),
+        }
+    }
+
+    syntax_tokens
+}
+
+fn build(events: Vec<Event>, tokens: Vec<SyntaxToken>) -> SyntaxNode {
+    let mut tokens = tokens.into_iter().enumerate().peekable();
+    let mut stack = vec![SyntaxNode {
+        kind: SyntaxKind::Program,
+        children: Vec::new(),
+    }];
+
+    for event in events.into_iter().chain(Some(Event::Finish(usize::MAX))) {
+        let until = match event {
+            Event::Start(_, index) | Event::Finish(index) => index,
+        };
+        while let Some((_, token)) = tokens.next_if(|(index, _)| *index < until) {
+            stack
+                .last_mut()
+                .unwrap()
+                .children
+                .push(SyntaxElement::Token(token));
+        }
+
+        match event {
+            Event::Start(kind, _) => stack.push(SyntaxNode {
+                kind,
+                children: Vec::new(),
+            }),
+            Event::Finish(..) if stack.len() > 1 => {
+                let node = stack.pop().unwrap();
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(SyntaxElement::Node(node));
+            }
+            Event::Finish(..) => {}
+        }
+    }
+
+    stack.pop().unwrap()
+}
@@ -0,0 +1,162 @@
+use crate::common::token::{Token, TokenType};
+
+const INDENT: &str = "    ";
+
+// Formats a token stream line by line. Statements keep the lines they were written on,
+// runs of blank lines are squashed into one, indentation follows the open brackets and
+// spacing inside a line is made canonical. Comments come from `Lexer::with_comments` and
+// stay where they were, with trailing comments separated by a single space.
+pub struct Formatter {
+    tokens: Vec<Token>,
+}
+
+impl Formatter {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens }
+    }
+
+    pub fn format(&self) -> String {
+        let mut output = String::new();
+        let mut depth: usize = 0;
+
+        for (blank_before, line) in self.lines() {
+            if blank_before && !output.is_empty() {
+                output.push('\n');
+            }
+
+            let closers = line
+                .iter()
+                .take_while(|token| Self::is_closer(&token.ttype))
+                .count();
+            output.push_str(&INDENT.repeat(depth.saturating_sub(closers)));
+
+            for (index, token) in line.iter().enumerate() {
+                if index > 0 {
+                    let before_previous = if index > 1 {
+                        Some(line[index - 2])
+                    } else {
+                        None
+                    };
+                    if Self::needs_space(before_previous, line[index - 1], token) {
+                        output.push(' ');
+                    }
+                }
+                if token.ttype == TokenType::Comment {
+                    output.push_str(token.lexeme.trim_end());
+                } else {
+                    output.push_str(&token.lexeme);
+                }
+
+                if Self::is_opener(&token.ttype) {
+                    depth += 1;
+                } else if Self::is_closer(&token.ttype) {
+                    depth = depth.saturating_sub(1);
+                }
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    // Groups the tokens by source line, remembering whether blank lines preceded each group.
+    // An `else` that starts a line is pulled up behind the `}` it belongs to, and so is a `{`
+    // that starts the body of an `if`, `else`, `func` or `test` from the line below it.
+    fn lines(&self) -> Vec<(bool, Vec<&Token>)> {
+        let mut lines: Vec<(bool, Vec<&Token>)> = Vec::new();
+        let mut last_row = 0;
+
+        for token in &self.tokens {
+            if token.ttype == TokenType::EOF {
+                break;
+            }
+
+            let row = token.position.row;
+            match lines.last_mut() {
+                Some((_, line)) if row == last_row => line.push(token),
+                Some((_, line))
+                    if token.ttype == TokenType::Else
+                        && line.last().map(|last| &last.ttype) == Some(&TokenType::CloseCurly) =>
+                {
+                    line.push(token)
+                }
+                Some((_, line))
+                    if token.ttype == TokenType::OpenCurly && Self::awaits_block(line) =>
+                {
+                    line.push(token)
+                }
+                _ => lines.push((last_row != 0 && row > last_row + 1, vec![token])),
+            }
+            last_row = row;
+        }
+
+        lines
+    }
+
+    fn awaits_block(line: &[&Token]) -> bool {
+        match line.last().map(|last| &last.ttype) {
+            Some(TokenType::Else) => true,
+            Some(TokenType::Comment) | None => false,
+            Some(_) => {
+                line.iter().any(|token| {
+                    matches!(
+                        token.ttype,
+                        TokenType::If | TokenType::Func | TokenType::Test
+                    )
+                }) && !line.iter().any(|token| token.ttype == TokenType::OpenCurly)
+            }
+        }
+    }
+
+    fn needs_space(before_previous: Option<&Token>, previous: &Token, current: &Token) -> bool {
+        match (&previous.ttype, &current.ttype) {
+            (_, TokenType::Comment) => true,
+            (_, TokenType::CloseParen | TokenType::CloseBrack | TokenType::Comma) => false,
+            (TokenType::OpenParen | TokenType::OpenBrack, _) => false,
+            (
+                TokenType::Identifier
+                | TokenType::Read
+                | TokenType::Write
+                | TokenType::Push
+                | TokenType::Pop
+                | TokenType::Assert
+                | TokenType::AssertEq,
+                TokenType::OpenParen,
+            ) => false,
+            (TokenType::Not, _) => false,
+            (TokenType::Minus, _) => !Self::is_unary(before_previous),
+            _ => true,
+        }
+    }
+
+    // A `-` is unary unless it follows something that ends an operand.
+    fn is_unary(before_operator: Option<&Token>) -> bool {
+        !matches!(
+            before_operator.map(|token| &token.ttype),
+            Some(
+                TokenType::Identifier
+                    | TokenType::Number
+                    | TokenType::String
+                    | TokenType::Boolean
+                    | TokenType::Nil
+                    | TokenType::CloseParen
+                    | TokenType::CloseBrack
+                    | TokenType::CloseCurly
+            )
+        )
+    }
+
+    fn is_opener(ttype: &TokenType) -> bool {
+        matches!(
+            ttype,
+            TokenType::OpenParen | TokenType::OpenBrack | TokenType::OpenCurly
+        )
+    }
+
+    fn is_closer(ttype: &TokenType) -> bool {
+        matches!(
+            ttype,
+            TokenType::CloseParen | TokenType::CloseBrack | TokenType::CloseCurly
+        )
+    }
+}
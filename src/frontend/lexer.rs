@@ -1,19 +1,42 @@
 use std::collections::HashMap;
 
 use crate::common::{
-    error::{Error, ErrorType},
+    error::{code, Error, ErrorType},
     object::{Meta, Object},
     position::Position,
     token::{Token, TokenType},
 };
 
+const KEYWORDS: &[(&str, TokenType)] = &[
+    ("let", TokenType::Let),
+    ("func", TokenType::Func),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("return", TokenType::Return),
+    ("test", TokenType::Test),
+    ("true", TokenType::Boolean),
+    ("false", TokenType::Boolean),
+    ("nil", TokenType::Nil),
+    ("read", TokenType::Read),
+    ("write", TokenType::Write),
+    ("push", TokenType::Push),
+    ("pop", TokenType::Pop),
+    ("assert", TokenType::Assert),
+    ("assert_eq", TokenType::AssertEq),
+];
+
 pub struct Lexer {
     source: Vec<char>,
 
     keywords: HashMap<String, TokenType>,
+    // The formatter needs comments and the syntax tree needs all trivia, everything else
+    // skips them.
+    comments: bool,
+    trivia: bool,
 
     start: usize,
     current: usize,
+    line_start: usize,
 
     current_position: Position,
 }
@@ -24,31 +47,37 @@ impl Lexer {
             source: source.chars().collect(),
 
             keywords: HashMap::new(),
+            comments: false,
+            trivia: false,
 
             start: 0,
             current: 0,
+            line_start: 0,
 
-            current_position: Position::new(source_path, 1),
+            current_position: Position::new(source_path, 1, 1, 0),
         }
     }
 
+    pub fn with_comments(mut self) -> Self {
+        self.comments = true;
+        self
+    }
+
+    // Keeps whitespace, newlines and comments as tokens, so the lexemes add up to the source.
+    pub fn with_trivia(mut self) -> Self {
+        self.comments = true;
+        self.trivia = true;
+        self
+    }
+
     fn init_keywords(&mut self) {
-        self.keywords.insert("let".to_string(), TokenType::Let);
-        self.keywords.insert("func".to_string(), TokenType::Func);
-        self.keywords.insert("if".to_string(), TokenType::If);
-        self.keywords.insert("else".to_string(), TokenType::Else);
-        self.keywords
-            .insert("return".to_string(), TokenType::Return);
-
-        self.keywords.insert("true".to_string(), TokenType::Boolean);
-        self.keywords
-            .insert("false".to_string(), TokenType::Boolean);
-        self.keywords.insert("nil".to_string(), TokenType::Nil);
-
-        self.keywords.insert("read".to_string(), TokenType::Read);
-        self.keywords.insert("write".to_string(), TokenType::Write);
-        self.keywords.insert("push".to_string(), TokenType::Push);
-        self.keywords.insert("pop".to_string(), TokenType::Pop);
+        for (keyword, ttype) in KEYWORDS {
+            self.keywords.insert(keyword.to_string(), ttype.clone());
+        }
+    }
+
+    pub fn keywords() -> impl Iterator<Item = &'static str> {
+        KEYWORDS.iter().map(|(keyword, _)| *keyword)
     }
 
     pub fn lex(&mut self) -> Result<Vec<Token>, Error> {
@@ -56,6 +85,11 @@ impl Lexer {
 
         let mut tokens = Vec::new();
 
+        // A leading `#!/usr/bin/env func` line lets scripts run as executables.
+        if self.source.starts_with(&['#', '!']) {
+            tokens.extend(self.make_comment()?);
+        }
+
         while !self.eof() {
             self.start = self.current;
             if let Some(token) = self.next_token()? {
@@ -63,6 +97,7 @@ impl Lexer {
             }
         }
 
+        self.start = self.current;
         tokens.push(self.token(TokenType::EOF, None));
         Ok(tokens)
     }
@@ -86,22 +121,36 @@ impl Lexer {
         }
     }
 
+    fn position(&self) -> Position {
+        let mut position = self.current_position.clone();
+        position.column = self.start - self.line_start + 1;
+        position.length = self.current - self.start;
+        position
+    }
+
     fn token(&mut self, ttype: TokenType, literal: Option<Object>) -> Token {
         let lexeme: String = self.source[self.start..self.current].iter().collect();
-        Token::new(ttype, lexeme, literal, self.current_position.clone())
+        Token::new(ttype, lexeme, literal, self.position())
     }
 
     fn next_token(&mut self) -> Result<Option<Token>, Error> {
         let current_char = self.peek();
         self.advance();
         match current_char {
-            ' ' | '\t' | '\r' => Ok(None),
+            ' ' | '\t' | '\r' => self.make_whitespace(),
 
             '+' => Ok(Some(self.token(TokenType::Plus, None))),
 
             '-' => Ok(Some(self.token(TokenType::Minus, None))),
 
-            '*' => Ok(Some(self.token(TokenType::Star, None))),
+            '*' => {
+                if self.peek() == '*' {
+                    self.advance();
+                    Ok(Some(self.token(TokenType::StarStar, None)))
+                } else {
+                    Ok(Some(self.token(TokenType::Star, None)))
+                }
+            }
 
             '%' => Ok(Some(self.token(TokenType::Modulo, None))),
 
@@ -128,7 +177,7 @@ impl Lexer {
             '/' => {
                 if self.peek() == '/' {
                     self.advance();
-                    self.ignore_comment()
+                    self.make_comment()
                 } else {
                     Ok(Some(self.token(TokenType::Slash, None)))
                 }
@@ -177,8 +226,9 @@ impl Lexer {
                 } else {
                     Err(Error::new(
                         ErrorType::LexingError,
+                        code::UNEXPECTED_CHARACTER,
                         format!("Unexpected charected `{}`", current_char),
-                        self.current_position.clone(),
+                        self.position(),
                     ))
                 }
             }
@@ -190,8 +240,9 @@ impl Lexer {
                 } else {
                     Err(Error::new(
                         ErrorType::LexingError,
+                        code::UNEXPECTED_CHARACTER,
                         format!("Unexpected charected `{}`", current_char),
-                        self.current_position.clone(),
+                        self.position(),
                     ))
                 }
             }
@@ -204,8 +255,9 @@ impl Lexer {
                 } else {
                     Err(Error::new(
                         ErrorType::LexingError,
+                        code::UNEXPECTED_CHARACTER,
                         format!("Unexpected charected `{}`", current_char),
-                        self.current_position.clone(),
+                        self.position(),
                     ))
                 }
             }
@@ -213,15 +265,36 @@ impl Lexer {
     }
 
     fn count_newline(&mut self) -> Result<Option<Token>, Error> {
+        let token = if self.trivia {
+            Some(self.token(TokenType::Newline, None))
+        } else {
+            None
+        };
         self.current_position.row += 1;
-        Ok(None)
+        self.line_start = self.current;
+        Ok(token)
+    }
+
+    fn make_whitespace(&mut self) -> Result<Option<Token>, Error> {
+        while matches!(self.peek(), ' ' | '\t' | '\r') && !self.eof() {
+            self.advance();
+        }
+        if self.trivia {
+            Ok(Some(self.token(TokenType::Whitespace, None)))
+        } else {
+            Ok(None)
+        }
     }
 
-    fn ignore_comment(&mut self) -> Result<Option<Token>, Error> {
+    fn make_comment(&mut self) -> Result<Option<Token>, Error> {
         while self.peek() != '\n' && !self.eof() {
             self.advance();
         }
-        Ok(None)
+        if self.comments {
+            Ok(Some(self.token(TokenType::Comment, None)))
+        } else {
+            Ok(None)
+        }
     }
 
     fn make_string(&mut self) -> Result<Option<Token>, Error> {
@@ -233,18 +306,21 @@ impl Lexer {
         }
         if self.peek() == '"' {
             self.advance();
-            let lexeme: String = self.source[self.start + 1..self.current - 1]
+            // The value holds what the escapes stand for, the lexeme keeps the source text.
+            let value: String = self.source[self.start + 1..self.current - 1]
                 .iter()
-                .collect();
+                .collect::<String>()
+                .replace("\\n", "\n");
             Ok(Some(self.token(
                 TokenType::String,
-                Some(Object::String(lexeme, Meta::default())),
+                Some(Object::String(value, Meta::default())),
             )))
         } else {
             Err(Error::new(
                 ErrorType::LexingError,
+                code::UNTERMINATED_STRING,
                 "Unterminated string".to_owned(),
-                self.current_position.clone(),
+                self.position(),
             ))
         }
     }
@@ -268,8 +344,9 @@ impl Lexer {
         } else {
             Err(Error::new(
                 ErrorType::LexingError,
+                code::INVALID_NUMBER,
                 format!("could not parse {} to float", lexeme),
-                self.current_position.clone(),
+                self.position(),
             ))
         }
     }
@@ -281,13 +358,15 @@ impl Lexer {
         let lexeme: String = self.source[self.start..self.current].iter().collect();
         if let Some(ttype) = self.keywords.get(&lexeme) {
             if ttype == &TokenType::Boolean && lexeme == "true" {
-                Ok(Some(
-                    self.token(TokenType::Boolean, Some(Object::Boolean(true, Meta::default()))),
-                ))
+                Ok(Some(self.token(
+                    TokenType::Boolean,
+                    Some(Object::Boolean(true, Meta::default())),
+                )))
             } else if ttype == &TokenType::Boolean && lexeme == "false" {
-                Ok(Some(
-                    self.token(TokenType::Boolean, Some(Object::Boolean(false, Meta::default()))),
-                ))
+                Ok(Some(self.token(
+                    TokenType::Boolean,
+                    Some(Object::Boolean(false, Meta::default())),
+                )))
             } else {
                 Ok(Some(self.token(ttype.clone(), None)))
             }
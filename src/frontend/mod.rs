@@ -1,2 +1,5 @@
+pub mod cst;
+pub mod formatter;
 pub mod lexer;
 pub mod parser;
+pub mod resolver;
@@ -3,21 +3,41 @@ use crate::common::{
         ArrayExpression, AssignmentStatement, BinaryExpression, BlockExpression, BuiltinFunction,
         BuiltinFunctionStatement, CallExpression, ElseBlock, Expression, FunctionStatement,
         GroupExpression, IdentifierExpression, IfExpression, LetStatement, LiteralExpression,
-        Program, Statement, UnaryExpression,
+        Program, Statement, TestStatement, UnaryExpression,
     },
-    error::{Error, ErrorType},
+    error::{code, Error, ErrorType},
     object::{Meta, Object},
     token::{Token, TokenType},
 };
 
+use super::cst::{Event, SyntaxKind};
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    // Where each syntax node starts and ends, the syntax tree is built from these.
+    events: Vec<Event>,
+}
+
+// Remembers where a node might start, for nodes like binary expressions whose kind is only
+// known after their first child has been parsed.
+#[derive(Clone, Copy)]
+struct Marker {
+    event: usize,
+    token: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            events: Vec::new(),
+        }
+    }
+
+    pub(crate) fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
     }
 
     pub fn parse(&mut self) -> Result<Program, Error> {
@@ -30,6 +50,26 @@ impl Parser {
         Ok(program)
     }
 
+    fn mark(&self) -> Marker {
+        Marker {
+            event: self.events.len(),
+            token: self.current,
+        }
+    }
+
+    fn start(&mut self, kind: SyntaxKind) {
+        self.events.push(Event::Start(kind, self.current));
+    }
+
+    fn precede(&mut self, marker: Marker, kind: SyntaxKind) {
+        self.events
+            .insert(marker.event, Event::Start(kind, marker.token));
+    }
+
+    fn finish(&mut self) {
+        self.events.push(Event::Finish(self.current));
+    }
+
     fn peek(&self) -> Token {
         self.tokens[self.current].clone()
     }
@@ -67,6 +107,7 @@ impl Parser {
         } else {
             Err(Error::new(
                 ErrorType::ParsingError,
+                code::EXPECTED_TOKEN,
                 format!("Expected `{}`, found `{}`", ttype, token.ttype),
                 token.position,
             ))
@@ -77,6 +118,7 @@ impl Parser {
         match self.peek().ttype {
             TokenType::Func => Ok(Statement::Function(self.function_statement()?)),
             TokenType::Let => Ok(Statement::Let(self.let_statement()?)),
+            TokenType::Test => Ok(Statement::Test(self.test_statement()?)),
             TokenType::Return => Ok(Statement::Return(self.return_statement()?)),
             TokenType::OpenCurly => Ok(Statement::Expression(Expression::Block(
                 self.block_expression()?,
@@ -87,6 +129,8 @@ impl Parser {
                     TokenType::Write,
                     TokenType::Push,
                     TokenType::Pop,
+                    TokenType::Assert,
+                    TokenType::AssertEq,
                 ]) {
                     Ok(Statement::BuiltinFunction(
                         self.builtin_function_statement()?,
@@ -103,13 +147,16 @@ impl Parser {
     }
 
     fn let_statement(&mut self) -> Result<LetStatement, Error> {
+        self.start(SyntaxKind::LetStatement);
         self.advance();
         let identifier = self.eat(TokenType::Identifier)?;
         if self.does_match(&[TokenType::Equal]) {
             self.advance();
             let expression = self.expression()?;
+            self.finish();
             Ok(LetStatement::new(identifier, expression))
         } else {
+            self.finish();
             Ok(LetStatement::new(
                 identifier,
                 Expression::Literal(LiteralExpression::new(Token::new(
@@ -123,14 +170,17 @@ impl Parser {
     }
 
     fn assignment_statement(&mut self) -> Result<AssignmentStatement, Error> {
+        self.start(SyntaxKind::AssignmentStatement);
         let identifier = self.eat(TokenType::Identifier)?;
         self.eat(TokenType::Equal)?;
         let expression = self.expression()?;
+        self.finish();
 
         Ok(AssignmentStatement::new(identifier, expression))
     }
 
     fn function_statement(&mut self) -> Result<FunctionStatement, Error> {
+        self.start(SyntaxKind::FunctionStatement);
         self.advance();
         let identifier = self.eat(TokenType::Identifier)?;
         let mut paramiters = Vec::new();
@@ -147,19 +197,35 @@ impl Parser {
         }
         self.eat(TokenType::CloseParen)?;
         let block = self.block_expression()?;
+        self.finish();
 
         Ok(FunctionStatement::new(identifier, paramiters, block, false))
     }
 
+    fn test_statement(&mut self) -> Result<TestStatement, Error> {
+        self.start(SyntaxKind::TestStatement);
+        self.advance();
+        let name = self.eat(TokenType::String)?;
+        if !self.does_match(&[TokenType::OpenCurly]) {
+            self.eat(TokenType::OpenCurly)?;
+        }
+        let block = self.block_expression()?;
+        self.finish();
+
+        Ok(TestStatement::new(name, block))
+    }
+
     fn builtin_function_statement(&mut self) -> Result<BuiltinFunctionStatement, Error> {
-        let func_type = self.next_token().ttype;
+        self.start(SyntaxKind::BuiltinFunctionStatement);
+        let keyword = self.next_token();
 
         self.eat(TokenType::OpenParen)?;
 
-        let builtin_func = match func_type {
+        let builtin_func = match keyword.ttype {
             TokenType::Read => {
                 let identifier = self.eat(TokenType::Identifier)?;
                 BuiltinFunctionStatement::new(
+                    keyword.clone(),
                     BuiltinFunction::Read,
                     vec![Expression::Identifier(IdentifierExpression::new(
                         identifier,
@@ -177,7 +243,7 @@ impl Parser {
                         break;
                     }
                 }
-                BuiltinFunctionStatement::new(BuiltinFunction::Write, arguments)
+                BuiltinFunctionStatement::new(keyword.clone(), BuiltinFunction::Write, arguments)
             }
 
             TokenType::Push => {
@@ -185,6 +251,7 @@ impl Parser {
                 self.eat(TokenType::Comma)?;
                 let identifier = self.eat(TokenType::Identifier)?;
                 BuiltinFunctionStatement::new(
+                    keyword.clone(),
                     BuiltinFunction::Push,
                     vec![
                         expression,
@@ -196,6 +263,7 @@ impl Parser {
             TokenType::Pop => {
                 let identifier = self.eat(TokenType::Identifier)?;
                 BuiltinFunctionStatement::new(
+                    keyword.clone(),
                     BuiltinFunction::Pop,
                     vec![Expression::Identifier(IdentifierExpression::new(
                         identifier,
@@ -203,14 +271,36 @@ impl Parser {
                 )
             }
 
+            TokenType::Assert => {
+                let mut arguments = vec![self.expression()?];
+                if self.does_match(&[TokenType::Comma]) {
+                    self.advance();
+                    arguments.push(self.expression()?);
+                }
+                BuiltinFunctionStatement::new(keyword.clone(), BuiltinFunction::Assert, arguments)
+            }
+
+            TokenType::AssertEq => {
+                let left = self.expression()?;
+                self.eat(TokenType::Comma)?;
+                let right = self.expression()?;
+                BuiltinFunctionStatement::new(
+                    keyword.clone(),
+                    BuiltinFunction::AssertEq,
+                    vec![left, right],
+                )
+            }
+
             _ => panic!(), // We're never reaching this because we've already filtered token type.
         };
 
         self.eat(TokenType::CloseParen)?;
+        self.finish();
         Ok(builtin_func)
     }
 
     fn if_expression(&mut self) -> Result<IfExpression, Error> {
+        self.start(SyntaxKind::IfExpression);
         self.advance();
         let condition = self.expression()?;
         let if_block = self.block_expression()?;
@@ -223,16 +313,21 @@ impl Parser {
                 else_block = Some(ElseBlock::Block(self.block_expression()?));
             }
         }
+        self.finish();
 
         Ok(IfExpression::new(condition, if_block, else_block))
     }
 
     fn return_statement(&mut self) -> Result<Expression, Error> {
+        self.start(SyntaxKind::ReturnStatement);
         self.advance();
-        self.expression()
+        let expression = self.expression()?;
+        self.finish();
+        Ok(expression)
     }
 
     fn block_expression(&mut self) -> Result<BlockExpression, Error> {
+        self.start(SyntaxKind::BlockExpression);
         self.advance();
         let mut statements = Vec::new();
         loop {
@@ -242,6 +337,7 @@ impl Parser {
             statements.push(self.statemet()?);
         }
         self.eat(TokenType::CloseCurly)?;
+        self.finish();
         Ok(BlockExpression::new(statements))
     }
 
@@ -260,11 +356,14 @@ impl Parser {
     }
 
     fn and(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
         let mut left = self.or()?;
 
         while self.does_match(&[TokenType::And]) {
             let operator = self.next_token();
             let right = self.or()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
             left = Expression::Binary(BinaryExpression::new(left, operator, right));
         }
 
@@ -272,11 +371,14 @@ impl Parser {
     }
 
     fn or(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
         let mut left = self.equality()?;
 
         while self.does_match(&[TokenType::Or]) {
             let operator = self.next_token();
             let right = self.equality()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
             left = Expression::Binary(BinaryExpression::new(left, operator, right));
         }
 
@@ -284,11 +386,14 @@ impl Parser {
     }
 
     fn equality(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
         let mut left = self.comparison()?;
 
         while self.does_match(&[TokenType::EqualEqual, TokenType::NotEqual]) {
             let operator = self.next_token();
             let right = self.comparison()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
             left = Expression::Binary(BinaryExpression::new(left, operator, right));
         }
 
@@ -296,6 +401,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
         let mut left = self.additive()?;
 
         while self.does_match(&[
@@ -306,6 +412,8 @@ impl Parser {
         ]) {
             let operator = self.next_token();
             let right = self.additive()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
             left = Expression::Binary(BinaryExpression::new(left, operator, right));
         }
 
@@ -313,11 +421,14 @@ impl Parser {
     }
 
     fn additive(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
         let mut left = self.multiplicative()?;
 
         while self.does_match(&[TokenType::Plus, TokenType::Minus]) {
             let operator = self.next_token();
             let right = self.multiplicative()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
             left = Expression::Binary(BinaryExpression::new(left, operator, right));
         }
 
@@ -325,11 +436,14 @@ impl Parser {
     }
 
     fn multiplicative(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
         let mut left = self.unary()?;
 
         while self.does_match(&[TokenType::Star, TokenType::Slash, TokenType::Modulo]) {
             let operator = self.next_token();
             let right = self.unary()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
             left = Expression::Binary(BinaryExpression::new(left, operator, right));
         }
 
@@ -338,11 +452,32 @@ impl Parser {
 
     fn unary(&mut self) -> Result<Expression, Error> {
         if self.does_match(&[TokenType::Minus, TokenType::Not]) {
+            self.start(SyntaxKind::UnaryExpression);
             let operator = self.next_token();
-            let right = self.primary()?;
+            let right = self.power()?;
+            self.finish();
             return Ok(Expression::Unary(UnaryExpression::new(operator, right)));
         }
-        self.primary()
+        self.power()
+    }
+
+    // `**` binds tighter than a unary operator on its left and groups to the right, so
+    // `-2 ** 2` is `-(2 ** 2)` and `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+    fn power(&mut self) -> Result<Expression, Error> {
+        let marker = self.mark();
+        let left = self.primary()?;
+
+        if self.does_match(&[TokenType::StarStar]) {
+            let operator = self.next_token();
+            let right = self.unary()?;
+            self.precede(marker, SyntaxKind::BinaryExpression);
+            self.finish();
+            return Ok(Expression::Binary(BinaryExpression::new(
+                left, operator, right,
+            )));
+        }
+
+        Ok(left)
     }
 
     fn primary(&mut self) -> Result<Expression, Error> {
@@ -352,12 +487,15 @@ impl Parser {
             TokenType::Boolean,
             TokenType::Nil,
         ]) {
-            Ok(Expression::Literal(LiteralExpression::new(
-                self.next_token(),
-            )))
+            self.start(SyntaxKind::LiteralExpression);
+            let literal = self.next_token();
+            self.finish();
+            Ok(Expression::Literal(LiteralExpression::new(literal)))
         } else if self.does_match(&[TokenType::Identifier]) {
+            let marker = self.mark();
             let identifier = self.next_token();
             if self.does_match(&[TokenType::OpenParen]) {
+                self.precede(marker, SyntaxKind::CallExpression);
                 self.advance();
                 let mut arguments = Vec::new();
                 if !self.does_match(&[TokenType::CloseParen]) {
@@ -371,13 +509,17 @@ impl Parser {
                     }
                 }
                 self.eat(TokenType::CloseParen)?;
+                self.finish();
                 Ok(Expression::Call(CallExpression::new(identifier, arguments)))
             } else {
+                self.precede(marker, SyntaxKind::IdentifierExpression);
+                self.finish();
                 Ok(Expression::Identifier(IdentifierExpression::new(
                     identifier,
                 )))
             }
         } else if self.does_match(&[TokenType::OpenBrack]) {
+            self.start(SyntaxKind::ArrayExpression);
             self.advance();
             let mut objects = Vec::new();
             loop {
@@ -390,16 +532,20 @@ impl Parser {
                 }
             }
             self.eat(TokenType::CloseBrack)?;
+            self.finish();
             Ok(Expression::Array(ArrayExpression::new(objects)))
         } else if self.does_match(&[TokenType::OpenParen]) {
+            self.start(SyntaxKind::GroupExpression);
             self.advance();
             let child = self.expression()?;
             self.eat(TokenType::CloseParen)?;
+            self.finish();
             Ok(Expression::Group(GroupExpression::new(child)))
         } else {
             let token = self.peek();
             Err(Error::new(
                 ErrorType::ParsingError,
+                code::UNEXPECTED_TOKEN,
                 format!("Unexpected token `{}`", token.ttype),
                 token.position,
             ))
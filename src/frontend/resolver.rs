@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    common::{
+        ast::{
+            BlockExpression, ElseBlock, Expression, FunctionStatement, IfExpression, Program,
+            Statement,
+        },
+        error::{code, Error, ErrorType},
+        token::Token,
+    },
+    runtime::stdlib,
+};
+
+// Checks names without running the program. Functions are global and may be called before
+// they're defined, variables are lexically scoped. Function bodies see the variables of
+// their caller, so inside them any variable declared somewhere in the program is accepted.
+#[derive(Default)]
+pub struct Resolver {
+    functions: HashMap<String, usize>,
+    declared: HashSet<String>,
+    scopes: Vec<HashSet<String>>,
+    function_depth: usize,
+    errors: Vec<Error>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn resolve(&mut self, program: &Program) -> Vec<Error> {
+        for statement in program {
+            self.collect_statement(statement);
+        }
+
+        // `args` and the constants are declared by the interpreter before the program starts.
+        let mut globals = HashSet::from(["args".to_string()]);
+        globals.extend(stdlib::constants().map(|(name, _)| name.to_string()));
+        self.scopes.push(globals);
+        for statement in program {
+            self.resolve_statement(statement);
+        }
+        self.scopes.pop();
+
+        std::mem::take(&mut self.errors)
+    }
+
+    fn collect_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let(let_statement) => {
+                self.declared
+                    .insert(let_statement.identifier.lexeme.clone());
+                self.collect_expression(&let_statement.expression);
+            }
+            Statement::Assignment(assignment_statement) => {
+                self.collect_expression(&assignment_statement.expression)
+            }
+            Statement::Function(function_statement) => {
+                self.functions.insert(
+                    function_statement.identifier.lexeme.clone(),
+                    function_statement.paramiters.len(),
+                );
+                for paramiter in &function_statement.paramiters {
+                    self.declared.insert(paramiter.lexeme.clone());
+                }
+                self.collect_block(&function_statement.block);
+            }
+            Statement::Test(test_statement) => self.collect_block(&test_statement.block),
+            Statement::BuiltinFunction(builtin_function_statement) => {
+                for argument in &builtin_function_statement.arguments {
+                    self.collect_expression(argument);
+                }
+            }
+            Statement::Return(expression) | Statement::Expression(expression) => {
+                self.collect_expression(expression)
+            }
+        }
+    }
+
+    fn collect_block(&mut self, block_expression: &BlockExpression) {
+        for statement in block_expression.statements.iter() {
+            self.collect_statement(statement);
+        }
+    }
+
+    fn collect_if(&mut self, if_expression: &IfExpression) {
+        self.collect_expression(&if_expression.condition);
+        self.collect_block(&if_expression.if_block);
+        match if_expression.else_block.as_ref() {
+            Some(ElseBlock::Block(block_expression)) => self.collect_block(block_expression),
+            Some(ElseBlock::If(if_expression)) => self.collect_if(if_expression),
+            None => {}
+        }
+    }
+
+    fn collect_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Block(block_expression) => self.collect_block(block_expression),
+            Expression::If(if_expression) => self.collect_if(if_expression),
+            Expression::Binary(binary_expression) => {
+                self.collect_expression(&binary_expression.left);
+                self.collect_expression(&binary_expression.right);
+            }
+            Expression::Unary(unary_expression) => self.collect_expression(&unary_expression.right),
+            Expression::Group(group_expression) => self.collect_expression(&group_expression.child),
+            Expression::Call(call_expression) => {
+                for argument in &call_expression.arguments {
+                    self.collect_expression(argument);
+                }
+            }
+            Expression::Identifier(..) | Expression::Literal(..) | Expression::Array(..) => {}
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let(let_statement) => {
+                self.resolve_expression(&let_statement.expression);
+                self.declare(&let_statement.identifier);
+            }
+            Statement::Assignment(assignment_statement) => {
+                self.resolve_variable(&assignment_statement.identifier);
+                self.resolve_expression(&assignment_statement.expression);
+            }
+            Statement::Function(function_statement) => self.resolve_function(function_statement),
+            Statement::Test(test_statement) => self.resolve_block(&test_statement.block),
+            Statement::BuiltinFunction(builtin_function_statement) => {
+                for argument in &builtin_function_statement.arguments {
+                    self.resolve_expression(argument);
+                }
+            }
+            Statement::Return(expression) | Statement::Expression(expression) => {
+                self.resolve_expression(expression)
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, function_statement: &FunctionStatement) {
+        self.function_depth += 1;
+        self.scopes.push(HashSet::new());
+        for paramiter in &function_statement.paramiters {
+            self.declare(paramiter);
+        }
+        self.resolve_block(&function_statement.block);
+        self.scopes.pop();
+        self.function_depth -= 1;
+    }
+
+    fn resolve_block(&mut self, block_expression: &BlockExpression) {
+        self.scopes.push(HashSet::new());
+        for statement in block_expression.statements.iter() {
+            self.resolve_statement(statement);
+        }
+        self.scopes.pop();
+    }
+
+    fn resolve_if(&mut self, if_expression: &IfExpression) {
+        self.resolve_expression(&if_expression.condition);
+        self.resolve_block(&if_expression.if_block);
+        match if_expression.else_block.as_ref() {
+            Some(ElseBlock::Block(block_expression)) => self.resolve_block(block_expression),
+            Some(ElseBlock::If(if_expression)) => self.resolve_if(if_expression),
+            None => {}
+        }
+    }
+
+    fn resolve_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Block(block_expression) => self.resolve_block(block_expression),
+            Expression::If(if_expression) => self.resolve_if(if_expression),
+            Expression::Binary(binary_expression) => {
+                self.resolve_expression(&binary_expression.left);
+                self.resolve_expression(&binary_expression.right);
+            }
+            Expression::Unary(unary_expression) => self.resolve_expression(&unary_expression.right),
+            Expression::Group(group_expression) => self.resolve_expression(&group_expression.child),
+            Expression::Call(call_expression) => {
+                self.resolve_call(&call_expression.identifier, call_expression.arguments.len());
+                for argument in &call_expression.arguments {
+                    self.resolve_expression(argument);
+                }
+            }
+            Expression::Identifier(identifier_expression) => {
+                self.resolve_variable(&identifier_expression.identifier)
+            }
+            Expression::Literal(..) | Expression::Array(..) => {}
+        }
+    }
+
+    fn declare(&mut self, identifier: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(identifier.lexeme.clone());
+        }
+    }
+
+    fn is_variable(&self, name: &str) -> bool {
+        let in_scope = self.scopes.iter().any(|scope| scope.contains(name));
+        let in_caller = self.function_depth > 0 && self.declared.contains(name);
+        in_scope || in_caller
+    }
+
+    fn resolve_variable(&mut self, identifier: &Token) {
+        // A function's name is a value that can be passed around.
+        let function = self.functions.contains_key(&identifier.lexeme)
+            || stdlib::find(&identifier.lexeme).is_some();
+        if !self.is_variable(&identifier.lexeme) && !function {
+            self.errors.push(Error::new(
+                ErrorType::NameError,
+                code::UNDEFINED_VARIABLE,
+                format!("Variable `{}` doesn't exist.", identifier.lexeme),
+                identifier.position.clone(),
+            ));
+        }
+    }
+
+    fn resolve_call(&mut self, identifier: &Token, arguments: usize) {
+        match self.functions.get(&identifier.lexeme) {
+            Some(&paramiters) if paramiters != arguments => self.errors.push(Error::new(
+                ErrorType::ArityError,
+                code::ARGUMENT_COUNT,
+                format!("Expected {} arguments, got {}", paramiters, arguments),
+                identifier.position.clone(),
+            )),
+            Some(..) => {}
+            None => {
+                if let Some(builtin) = stdlib::find(&identifier.lexeme) {
+                    if let Err(err) = builtin.check_arity(arguments, &identifier.position) {
+                        self.errors.push(err);
+                    }
+                    return;
+                }
+                // A variable may hold a function, it's checked once called.
+                if self.is_variable(&identifier.lexeme) {
+                    return;
+                }
+                self.errors.push(Error::new(
+                    ErrorType::NameError,
+                    code::UNDEFINED_FUNCTION,
+                    format!("Function `{}` doesn't exist.", identifier.lexeme),
+                    identifier.position.clone(),
+                ));
+            }
+        }
+    }
+}
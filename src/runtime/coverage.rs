@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::common::{
+    ast::{ElseBlock, Expression, IfExpression, Program, Statement},
+    error::Error,
+    position::Position,
+};
+
+use super::{hook::Hook, interpreter::Interpreter};
+
+#[derive(Debug, Default, Clone)]
+pub struct FileCoverage {
+    // How often each line that starts a statement ran.
+    pub lines: BTreeMap<usize, usize>,
+    // How often each arm of an `if` ran, the `if` arm first, keyed by the row and column of
+    // its condition.
+    pub branches: BTreeMap<(usize, usize), [usize; 2]>,
+}
+
+impl FileCoverage {
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+
+    pub fn branches_hit(&self) -> usize {
+        self.branches
+            .values()
+            .flatten()
+            .filter(|hits| **hits > 0)
+            .count()
+    }
+
+    // The lines that never ran, with runs of consecutive ones joined, e.g. `4-6, 9`.
+    pub fn missed(&self) -> String {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for (line, _) in self.lines.iter().filter(|(_, hits)| **hits == 0) {
+            match ranges.last_mut() {
+                Some(range) if range.1 + 1 == *line => range.1 = *line,
+                _ => ranges.push((*line, *line)),
+            }
+        }
+        ranges
+            .iter()
+            .map(|(first, last)| {
+                if first == last {
+                    first.to_string()
+                } else {
+                    format!("{}-{}", first, last)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+// Records which statements and `if` arms ran, per file. Programs added with `add_program`
+// also list what never ran.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    pub files: BTreeMap<String, FileCoverage>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_program(&mut self, program: &Program) {
+        for statement in program {
+            self.add_statement(statement);
+        }
+    }
+
+    fn add_statement(&mut self, statement: &Statement) {
+        // Tests are run by name, they never run as statements themselves.
+        let runs = !matches!(statement, Statement::Test(..));
+        if let Some(position) = statement.position().filter(|_| runs) {
+            self.file(position).lines.entry(position.row).or_insert(0);
+        }
+        match statement {
+            Statement::Let(let_statement) => self.add_expression(&let_statement.expression),
+            Statement::Assignment(assignment_statement) => {
+                self.add_expression(&assignment_statement.expression)
+            }
+            Statement::Function(function_statement) => {
+                self.add_program(&function_statement.block.statements)
+            }
+            Statement::Test(test_statement) => self.add_program(&test_statement.block.statements),
+            Statement::BuiltinFunction(builtin_function_statement) => {
+                for argument in &builtin_function_statement.arguments {
+                    self.add_expression(argument);
+                }
+            }
+            Statement::Return(expression) | Statement::Expression(expression) => {
+                self.add_expression(expression)
+            }
+        }
+    }
+
+    fn add_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Block(block_expression) => self.add_program(&block_expression.statements),
+            Expression::If(if_expression) => self.add_if(if_expression),
+            Expression::Binary(binary_expression) => {
+                self.add_expression(&binary_expression.left);
+                self.add_expression(&binary_expression.right);
+            }
+            Expression::Unary(unary_expression) => self.add_expression(&unary_expression.right),
+            Expression::Group(group_expression) => self.add_expression(&group_expression.child),
+            Expression::Call(call_expression) => {
+                for argument in &call_expression.arguments {
+                    self.add_expression(argument);
+                }
+            }
+            Expression::Identifier(..) | Expression::Literal(..) | Expression::Array(..) => {}
+        }
+    }
+
+    fn add_if(&mut self, if_expression: &IfExpression) {
+        if let Some(position) = if_expression.condition.position() {
+            self.file(position)
+                .branches
+                .entry((position.row, position.column))
+                .or_insert([0, 0]);
+        }
+        self.add_expression(&if_expression.condition);
+        self.add_program(&if_expression.if_block.statements);
+        match &*if_expression.else_block {
+            Some(ElseBlock::Block(block_expression)) => {
+                self.add_program(&block_expression.statements)
+            }
+            Some(ElseBlock::If(if_expression)) => self.add_if(if_expression),
+            None => {}
+        }
+    }
+
+    fn file(&mut self, position: &Position) -> &mut FileCoverage {
+        self.files.entry(position.source_path.clone()).or_default()
+    }
+
+    // One row per file with its line and branch coverage and the lines that never ran.
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        writeln!(
+            summary,
+            "{:<32} {:>16} {:>16}   missed lines",
+            "file", "lines", "branches"
+        )
+        .ok();
+        for (source_path, file) in &self.files {
+            writeln!(
+                summary,
+                "{:<32} {:>16} {:>16}   {}",
+                source_path,
+                ratio(file.lines_hit(), file.lines.len()),
+                ratio(file.branches_hit(), file.branches.len() * 2),
+                file.missed()
+            )
+            .ok();
+        }
+        summary
+    }
+
+    // The tracefile format of LCOV, which coverage viewers and CI services read.
+    pub fn lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (source_path, file) in &self.files {
+            writeln!(lcov, "TN:\nSF:{}", source_path).ok();
+
+            let mut block = 0;
+            let mut last_row = 0;
+            for ((row, _), arms) in &file.branches {
+                // Blocks number the `if`s of a line.
+                block = if *row == last_row { block + 1 } else { 0 };
+                last_row = *row;
+                let ran = arms.iter().any(|hits| *hits > 0);
+                for (branch, hits) in arms.iter().enumerate() {
+                    // `-` marks branches whose `if` never ran.
+                    let taken = if ran {
+                        hits.to_string()
+                    } else {
+                        "-".to_string()
+                    };
+                    writeln!(lcov, "BRDA:{},{},{},{}", row, block, branch, taken).ok();
+                }
+            }
+            writeln!(
+                lcov,
+                "BRF:{}\nBRH:{}",
+                file.branches.len() * 2,
+                file.branches_hit()
+            )
+            .ok();
+
+            for (line, hits) in &file.lines {
+                writeln!(lcov, "DA:{},{}", line, hits).ok();
+            }
+            writeln!(
+                lcov,
+                "LF:{}\nLH:{}\nend_of_record",
+                file.lines.len(),
+                file.lines_hit()
+            )
+            .ok();
+        }
+        lcov
+    }
+}
+
+fn ratio(hit: usize, found: usize) -> String {
+    if found == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{}/{} {:>5.1}%",
+        hit,
+        found,
+        hit as f64 * 100.0 / found as f64
+    )
+}
+
+impl Hook for Coverage {
+    fn before_statement(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        statement: &Statement,
+    ) -> Result<(), Error> {
+        if let Some(position) = statement.position() {
+            *self.file(position).lines.entry(position.row).or_insert(0) += 1;
+        }
+        Ok(())
+    }
+
+    fn branch(&mut self, _interpreter: &mut Interpreter, condition: &Position, taken: bool) {
+        let arms = self
+            .file(condition)
+            .branches
+            .entry((condition.row, condition.column))
+            .or_insert([0, 0]);
+        arms[if taken { 0 } else { 1 }] += 1;
+    }
+}
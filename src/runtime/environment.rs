@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::common::{
     ast::FunctionStatement,
-    error::{Error, ErrorType},
+    error::{code, Error, ErrorType},
     object::Object,
     token::Token,
 };
@@ -14,7 +14,15 @@ pub struct VariableBindings {
 
 impl VariableBindings {
     pub fn declare(&mut self, identifier: Token, value: Object) {
-        self.bindings.insert(identifier.lexeme, value);
+        self.define(identifier.lexeme, value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Object)> {
+        self.bindings.iter()
+    }
+
+    pub fn define(&mut self, name: String, value: Object) {
+        self.bindings.insert(name, value);
     }
 
     pub fn get(&self, identifier: Token) -> Result<Object, Error> {
@@ -22,20 +30,34 @@ impl VariableBindings {
             Ok(value.clone())
         } else {
             Err(Error::new(
-                ErrorType::RuntimeError,
+                ErrorType::NameError,
+                code::UNDEFINED_VARIABLE,
                 format!("Variable `{}` doesn't exist.", identifier.lexeme),
                 identifier.position,
             ))
         }
     }
 
+    pub fn get_mut(&mut self, identifier: Token) -> Result<&mut Object, Error> {
+        match self.bindings.get_mut(&identifier.lexeme) {
+            Some(value) => Ok(value),
+            None => Err(Error::new(
+                ErrorType::NameError,
+                code::UNDEFINED_VARIABLE,
+                format!("Variable `{}` doesn't exist.", identifier.lexeme),
+                identifier.position,
+            )),
+        }
+    }
+
     pub fn assign(&mut self, identifier: Token, value: Object) -> Result<(), Error> {
-        if self.bindings.get(&identifier.lexeme).is_some() {
+        if self.bindings.contains_key(&identifier.lexeme) {
             self.declare(identifier, value);
             Ok(())
         } else {
             Err(Error::new(
-                ErrorType::RuntimeError,
+                ErrorType::NameError,
+                code::UNDEFINED_VARIABLE,
                 format!("Variable `{}` doesn't exist.", identifier.lexeme),
                 identifier.position,
             ))
@@ -53,12 +75,21 @@ impl FunctionBindings {
         self.bindings.insert(identifier.lexeme, value);
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &FunctionStatement)> {
+        self.bindings.iter()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.bindings.contains_key(name)
+    }
+
     pub fn get(&self, identifier: Token) -> Result<FunctionStatement, Error> {
         if let Some(value) = self.bindings.get(&identifier.lexeme) {
             Ok(value.clone())
         } else {
             Err(Error::new(
-                ErrorType::RuntimeError,
+                ErrorType::NameError,
+                code::UNDEFINED_FUNCTION,
                 format!("Function `{}` doesn't exist.", identifier.lexeme),
                 identifier.position,
             ))
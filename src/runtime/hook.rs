@@ -0,0 +1,103 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::common::{
+    ast::{Expression, Statement},
+    error::Error,
+    object::Object,
+    position::Position,
+};
+
+use super::interpreter::Interpreter;
+
+// Lets tools like the debugger watch a program run. The interpreter lends itself to the
+// hook, so a hook can inspect bindings, the call stack and even evaluate code, hooks are
+// not called for what runs while they hold the interpreter.
+pub trait Hook {
+    // Called before every statement, including `return`s. An error stops the program.
+    fn before_statement(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _statement: &Statement,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // Called with the value of every statement that ran without an error.
+    fn after_statement(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _statement: &Statement,
+        _value: &Object,
+    ) {
+    }
+
+    // Called with the value of every expression that was evaluated without an error.
+    fn after_expression(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _expression: &Expression,
+        _value: &Object,
+    ) {
+    }
+
+    // Called once the arguments of a call to a user function are bound.
+    fn enter_function(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        _name: &str,
+        _position: &Position,
+    ) {
+    }
+
+    // Called when a user function returns, or fails.
+    fn exit_function(&mut self, _interpreter: &mut Interpreter, _name: &str) {}
+
+    // Called once the condition of an `if` is evaluated, `taken` tells whether the `if` arm
+    // runs rather than the `else` arm (or nothing).
+    fn branch(&mut self, _interpreter: &mut Interpreter, _condition: &Position, _taken: bool) {}
+}
+
+// Lets the owner of a hook keep a handle to it, to read what it collected once the program
+// is done.
+impl<T: Hook> Hook for Rc<RefCell<T>> {
+    fn before_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        statement: &Statement,
+    ) -> Result<(), Error> {
+        self.borrow_mut().before_statement(interpreter, statement)
+    }
+
+    fn after_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        statement: &Statement,
+        value: &Object,
+    ) {
+        self.borrow_mut()
+            .after_statement(interpreter, statement, value)
+    }
+
+    fn after_expression(
+        &mut self,
+        interpreter: &mut Interpreter,
+        expression: &Expression,
+        value: &Object,
+    ) {
+        self.borrow_mut()
+            .after_expression(interpreter, expression, value)
+    }
+
+    fn enter_function(&mut self, interpreter: &mut Interpreter, name: &str, position: &Position) {
+        self.borrow_mut()
+            .enter_function(interpreter, name, position)
+    }
+
+    fn exit_function(&mut self, interpreter: &mut Interpreter, name: &str) {
+        self.borrow_mut().exit_function(interpreter, name)
+    }
+
+    fn branch(&mut self, interpreter: &mut Interpreter, condition: &Position, taken: bool) {
+        self.borrow_mut().branch(interpreter, condition, taken)
+    }
+}
@@ -1,23 +1,61 @@
-use std::io::stdin;
+use std::{
+    io::{stdin, stdout, BufRead, BufReader, Write},
+    time::Instant,
+};
 
 use crate::common::{
     ast::{
         AssignmentStatement, BinaryExpression, BlockExpression, BuiltinFunction,
         BuiltinFunctionStatement, CallExpression, ElseBlock, Expression, FunctionStatement,
         GroupExpression, IdentifierExpression, IfExpression, LetStatement, Program, Statement,
-        UnaryExpression,
+        TestStatement, UnaryExpression,
     },
-    error::{Error, ErrorType},
+    error::{code, Error, ErrorType},
     object::{Meta, Object},
-    token::TokenType,
+    position::Position,
+    token::{Token, TokenType},
+};
+
+use super::{
+    environment::{FunctionBindings, VariableBindings},
+    hook::Hook,
+    limits::{Capabilities, InterruptHandle, Limits},
+    stdlib::{self, Builtin},
 };
 
-use super::environment::{FunctionBindings, VariableBindings};
+pub struct TestResult {
+    pub name: String,
+    pub position: Position,
+    pub error: Option<Error>,
+}
+
+// A call to a user function that hasn't returned yet.
+pub struct Frame {
+    pub name: String,
+    pub position: Position,
+    // The bindings of the caller, they are restored when the call returns.
+    caller_variables: VariableBindings,
+}
 
-#[derive(Default)]
 pub struct Interpreter {
     variables: VariableBindings,
     functions: FunctionBindings,
+    frames: Vec<Frame>,
+    hooks: Vec<Box<dyn Hook>>,
+    limits: Limits,
+    capabilities: Capabilities,
+    steps: u64,
+    deadline: Option<Instant>,
+    interrupt: InterruptHandle,
+
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::with_io(BufReader::new(stdin()), stdout())
+    }
 }
 
 impl Interpreter {
@@ -25,14 +63,286 @@ impl Interpreter {
         Self::default()
     }
 
+    pub fn with_io(input: impl BufRead + 'static, output: impl Write + 'static) -> Self {
+        Self {
+            variables: VariableBindings::default(),
+            functions: FunctionBindings::default(),
+            frames: Vec::new(),
+            hooks: Vec::new(),
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            steps: 0,
+            deadline: None,
+            interrupt: InterruptHandle::default(),
+
+            input: Box::new(input),
+            output: Box::new(output),
+        }
+        .with_args(Vec::new())
+    }
+
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        let args = args
+            .into_iter()
+            .map(|arg| Object::String(arg, Meta::default()))
+            .collect();
+        self.variables
+            .define("args".to_string(), Object::Array(args, Meta::default()));
+        self
+    }
+
+    pub fn with_hook(mut self, hook: impl Hook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.steps = 0;
+        self.deadline = limits.time.map(|time| Instant::now() + time);
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    pub fn variables(&self) -> Vec<(&str, &Object)> {
+        Self::sorted(&self.variables)
+    }
+
+    // The calls that haven't returned yet, the innermost last.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    // The bindings seen by the frame `depth` calls away from the innermost one, the top level
+    // being `frames().len()` calls away.
+    pub fn frame_variables(&self, depth: usize) -> Option<Vec<(&str, &Object)>> {
+        match depth {
+            0 => Some(self.variables()),
+            depth => self
+                .frames
+                .len()
+                .checked_sub(depth)
+                .map(|index| Self::sorted(&self.frames[index].caller_variables)),
+        }
+    }
+
+    fn sorted(bindings: &VariableBindings) -> Vec<(&str, &Object)> {
+        let mut variables: Vec<(&str, &Object)> = bindings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+            .collect();
+        variables.sort_by_key(|(name, _)| *name);
+        variables
+    }
+
+    pub fn functions(&self) -> Vec<&FunctionStatement> {
+        let mut functions: Vec<&FunctionStatement> = self
+            .functions
+            .iter()
+            .map(|(_, function)| function)
+            .collect();
+        functions.sort_by(|a, b| a.identifier.lexeme.cmp(&b.identifier.lexeme));
+        functions
+    }
+
     pub fn interpret(&mut self, program: Program) -> Result<(), Error> {
+        self.evaluate(program)?;
+        Ok(())
+    }
+
+    // Like `interpret`, but gives back the value of the last statement.
+    pub fn evaluate(&mut self, program: Program) -> Result<Object, Error> {
+        // Interrupts only stop a running program, one that came while idle is dropped.
+        self.interrupt.take();
+        let mut value = Object::Nil(Meta::default());
         for statement in program {
-            self.execute_statement(statement)?;
+            match self.execute_statement(statement) {
+                Ok(object) => value = object,
+                Err(err) => {
+                    self.output.flush().ok();
+                    return Err(err);
+                }
+            }
         }
-        Ok(())
+        self.output
+            .flush()
+            .map_err(|err| Self::io_error(err, Position::new("stdout".to_string(), 0, 0, 0)))?;
+        Ok(value)
+    }
+
+    // Only the functions a file declares are visible to its tests, the rest of its top level
+    // statements are skipped so testing a script never reads input or writes output.
+    pub fn run_tests(&mut self, program: Program) -> Result<Vec<TestResult>, Error> {
+        let mut tests = Vec::new();
+        for statement in program {
+            match statement {
+                Statement::Test(test_statement) => tests.push(test_statement),
+                Statement::Function(..) => {
+                    self.execute_statement(statement)?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(tests.into_iter().map(|test| self.run_test(test)).collect())
+    }
+
+    fn run_test(&mut self, test_statement: TestStatement) -> TestResult {
+        let old_variables = self.variables.clone();
+        let error = self
+            .evaluate_block_expression(test_statement.block)
+            .and_then(|_| {
+                self.output
+                    .flush()
+                    .map_err(|err| Self::io_error(err, test_statement.name.position.clone()))
+            })
+            .err();
+        self.variables = old_variables;
+
+        TestResult {
+            name: test_statement.name.lexeme.trim_matches('"').to_string(),
+            position: test_statement.name.position,
+            error,
+        }
+    }
+
+    fn io_error(err: std::io::Error, position: Position) -> Error {
+        Error::new(
+            ErrorType::IoError,
+            code::IO_FAILURE,
+            format!("I/O operation failed: {}", err),
+            position,
+        )
+    }
+
+    // Counts a statement or expression against the step and time limits.
+    fn step(&mut self, position: Option<&Position>) -> Result<(), Error> {
+        self.steps += 1;
+        let (code, message) = match (self.limits.steps, self.deadline) {
+            (Some(steps), _) if self.steps > steps => (
+                code::STEP_LIMIT,
+                format!("The program took more than {} steps", steps),
+            ),
+            (_, Some(deadline)) if Instant::now() > deadline => (
+                code::TIME_LIMIT,
+                format!(
+                    "The program ran longer than {}ms",
+                    self.limits.time.unwrap_or_default().as_millis()
+                ),
+            ),
+            _ => return Ok(()),
+        };
+        let position = position
+            .cloned()
+            .unwrap_or_else(|| Position::new(String::new(), 0, 0, 0));
+        Err(Error::new(ErrorType::RuntimeError, code, message, position))
+    }
+
+    fn check_size(&self, value: &Object, position: &Position) -> Result<(), Error> {
+        match value {
+            Object::String(string, ..) => {
+                self.check_length(value.type_name(), string.len(), position)
+            }
+            Object::Array(array, ..) => self.check_length(value.type_name(), array.len(), position),
+            _ => Ok(()),
+        }
+    }
+
+    // Lets builtins refuse a string or array before building it.
+    pub(crate) fn check_length(
+        &self,
+        type_name: &str,
+        size: usize,
+        position: &Position,
+    ) -> Result<(), Error> {
+        match self.limits.size {
+            Some(limit) if size > limit => Err(Error::new(
+                ErrorType::RuntimeError,
+                code::SIZE_LIMIT,
+                format!(
+                    "A `{}` of size {} is larger than the limit of {}",
+                    type_name, size, limit
+                ),
+                position.clone(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Fails unless the program may use what `allowed` tells about.
+    pub(crate) fn require(
+        &self,
+        allowed: fn(&Capabilities) -> bool,
+        what: &str,
+        position: &Position,
+    ) -> Result<(), Error> {
+        if allowed(&self.capabilities) {
+            return Ok(());
+        }
+        Err(Error::new(
+            ErrorType::RuntimeError,
+            code::NOT_ALLOWED,
+            format!("{} is not allowed here", what),
+            position.clone(),
+        ))
+    }
+
+    // Lends the interpreter to every hook in turn. Hooks added meanwhile are kept.
+    fn run_hooks(
+        &mut self,
+        mut call: impl FnMut(&mut dyn Hook, &mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        if self.hooks.is_empty() {
+            return Ok(());
+        }
+        let mut hooks = std::mem::take(&mut self.hooks);
+        let result = hooks
+            .iter_mut()
+            .try_for_each(|hook| call(hook.as_mut(), self));
+        hooks.append(&mut self.hooks);
+        self.hooks = hooks;
+        result
     }
 
     fn execute_statement(&mut self, statement: Statement) -> Result<Object, Error> {
+        self.step(statement.position())?;
+        if self.interrupt.take() {
+            return Err(Error::new(
+                ErrorType::RuntimeError,
+                code::INTERRUPTED,
+                "Interrupted".to_string(),
+                statement
+                    .position()
+                    .cloned()
+                    .unwrap_or_else(|| Position::new(String::new(), 0, 0, 0)),
+            ));
+        }
+        if self.hooks.is_empty() {
+            return self.match_statement(statement);
+        }
+
+        self.run_hooks(|hook, interpreter| hook.before_statement(interpreter, &statement))?;
+        let value = self.match_statement(statement.clone())?;
+        self.run_hooks(|hook, interpreter| {
+            hook.after_statement(interpreter, &statement, &value);
+            Ok(())
+        })?;
+        Ok(value)
+    }
+
+    fn match_statement(&mut self, statement: Statement) -> Result<Object, Error> {
         match statement {
             Statement::Let(let_statement) => self.execute_let_statement(let_statement),
 
@@ -49,6 +359,8 @@ impl Interpreter {
             }
 
             Statement::Expression(expression) => self.evaluate_expression(expression),
+            // Tests only run through `run_tests`.
+            Statement::Test(..) => Ok(Object::Nil(Meta::default())),
             _ => Ok(Object::Nil(Meta::default())),
         }
     }
@@ -86,17 +398,57 @@ impl Interpreter {
         &mut self,
         arguments: Vec<Expression>,
         function_statement: FunctionStatement,
+        position: Position,
     ) -> Result<Object, Error> {
-        let old_variables = self.variables.clone();
+        let mut values = Vec::new();
+        for argument in arguments {
+            values.push(self.evaluate_expression(argument)?);
+        }
+        self.call_function_statement(values, function_statement, position)
+    }
+
+    fn call_function_statement(
+        &mut self,
+        arguments: Vec<Object>,
+        function_statement: FunctionStatement,
+        position: Position,
+    ) -> Result<Object, Error> {
+        if let Some(depth) = self.limits.depth {
+            if self.frames.len() >= depth {
+                return Err(Error::new(
+                    ErrorType::RuntimeError,
+                    code::DEPTH_LIMIT,
+                    format!("Calls nested deeper than {} levels", depth),
+                    position,
+                ));
+            }
+        }
+        let caller_variables = self.variables.clone();
 
-        for (identifier, argument) in function_statement.paramiters.iter().zip(arguments.iter()) {
-            let value = self.evaluate_expression(argument.clone())?;
+        for (identifier, value) in function_statement.paramiters.iter().zip(arguments) {
             self.variables.declare(identifier.clone(), value);
         }
-        let return_value = self.evaluate_block_expression(function_statement.block)?;
 
-        self.variables = old_variables;
-        Ok(return_value)
+        let name = function_statement.identifier.lexeme;
+        self.frames.push(Frame {
+            name: name.clone(),
+            position: position.clone(),
+            caller_variables,
+        });
+        self.run_hooks(|hook, interpreter| {
+            hook.enter_function(interpreter, &name, &position);
+            Ok(())
+        })?;
+        let return_value = self.evaluate_block_expression(function_statement.block);
+        self.run_hooks(|hook, interpreter| {
+            hook.exit_function(interpreter, &name);
+            Ok(())
+        })?;
+
+        if let Some(frame) = self.frames.pop() {
+            self.variables = frame.caller_variables;
+        }
+        return_value
     }
 
     fn execute_builtin_function_statement(
@@ -109,17 +461,26 @@ impl Interpreter {
                     Expression::Identifier(identifier) => identifier.identifier,
                     _ => panic!(), // We're never reaching this because we're 'eating' identifier token in parser.
                 };
+                let position = builtin_function_statement.keyword.position;
+                self.require(|capabilities| capabilities.read, "`read`", &position)?;
+                self.output
+                    .flush()
+                    .map_err(|err| Self::io_error(err, position.clone()))?;
                 let mut value = String::new();
-                stdin().read_line(&mut value).unwrap();
-                self.variables.assign(
-                    identifier,
-                    Object::String(value.trim().to_string(), Meta::default()),
-                )?;
+                self.input
+                    .read_line(&mut value)
+                    .map_err(|err| Self::io_error(err, position.clone()))?;
+                let value = Object::String(value.trim().to_string(), Meta::default());
+                self.check_size(&value, &position)?;
+                self.variables.assign(identifier, value)?;
             }
 
             BuiltinFunction::Write => {
+                let position = builtin_function_statement.keyword.position;
                 for argument in builtin_function_statement.arguments {
-                    print!("{}", self.evaluate_expression(argument)?);
+                    let value = self.evaluate_expression(argument)?;
+                    write!(self.output, "{}", value)
+                        .map_err(|err| Self::io_error(err, position.clone()))?;
                 }
             }
 
@@ -130,9 +491,14 @@ impl Interpreter {
                 };
                 let object =
                     self.evaluate_expression(builtin_function_statement.arguments[0].clone())?;
-                let mut array = self.variables.get(identifier.clone())?;
-                self.variables
-                    .assign(identifier.clone(), array.push(object, identifier.position)?)?;
+                // The array grows where it is stored instead of being copied.
+                let size = match self.variables.get_mut(identifier.clone())? {
+                    Object::Array(array, ..) => array.len() + 1,
+                    _ => 0,
+                };
+                self.check_length("array", size, &identifier.position)?;
+                let position = identifier.position.clone();
+                self.variables.get_mut(identifier)?.push(object, position)?;
             }
 
             BuiltinFunction::Pop => {
@@ -140,9 +506,44 @@ impl Interpreter {
                     Expression::Identifier(identifier) => identifier.identifier,
                     _ => panic!(), // We're never reaching this because we're 'eating' identifier token in parser.
                 };
-                let mut array = self.variables.get(identifier.clone())?;
-                self.variables
-                    .assign(identifier.clone(), array.pop(identifier.position)?)?;
+                let position = identifier.position.clone();
+                self.variables.get_mut(identifier)?.pop(position)?;
+            }
+
+            BuiltinFunction::Assert => {
+                let mut arguments = builtin_function_statement.arguments.into_iter();
+                let condition = self.evaluate_expression(arguments.next().unwrap())?;
+                if !condition.is_truthy() {
+                    let message = match arguments.next() {
+                        Some(message) => {
+                            format!("Assertion failed: {}", self.evaluate_expression(message)?)
+                        }
+                        None => format!("Assertion failed, condition was `{}`", condition),
+                    };
+                    return Err(Error::new(
+                        ErrorType::AssertionError,
+                        code::ASSERTION_FAILED,
+                        message,
+                        builtin_function_statement.keyword.position,
+                    ));
+                }
+            }
+
+            BuiltinFunction::AssertEq => {
+                let mut arguments = builtin_function_statement.arguments.into_iter();
+                let left = self.evaluate_expression(arguments.next().unwrap())?;
+                let right = self.evaluate_expression(arguments.next().unwrap())?;
+                if left != right {
+                    return Err(Error::new(
+                        ErrorType::AssertionError,
+                        code::ASSERTION_FAILED,
+                        format!(
+                            "Assertion failed, left: `{}` is not equal to right: `{}`",
+                            left, right
+                        ),
+                        builtin_function_statement.keyword.position,
+                    ));
+                }
             }
         }
 
@@ -150,7 +551,14 @@ impl Interpreter {
     }
 
     fn evaluate_if_expression(&mut self, if_statement: IfExpression) -> Result<Object, Error> {
+        let position = if_statement.condition.position().cloned();
         let condition = self.evaluate_expression(*if_statement.condition)?;
+        if let Some(position) = position {
+            self.run_hooks(|hook, interpreter| {
+                hook.branch(interpreter, &position, condition.is_truthy());
+                Ok(())
+            })?;
+        }
         if condition.is_truthy() {
             self.evaluate_block_expression(if_statement.if_block)
         } else if let Some(else_block) = *if_statement.else_block {
@@ -168,33 +576,62 @@ impl Interpreter {
         block_expression: BlockExpression,
     ) -> Result<Object, Error> {
         let old_variables = self.variables.clone();
+        let return_value = self.execute_block_statements(block_expression);
+        // Also when a statement failed, so an error `try` or the REPL recovers from doesn't
+        // leave the block's bindings behind.
+        self.variables = old_variables;
+        return_value
+    }
+
+    fn execute_block_statements(
+        &mut self,
+        block_expression: BlockExpression,
+    ) -> Result<Object, Error> {
         let mut return_value = Object::Nil(Meta::default());
         for statement in *block_expression.statements {
-            if let Statement::Return(return_expression) = statement {
-                return_value = self.evaluate_expression(return_expression)?;
+            if let Statement::Return(return_expression) = &statement {
+                // `return` doesn't go through `execute_statement`, hooks still see it.
+                self.run_hooks(|hook, interpreter| hook.before_statement(interpreter, &statement))?;
+                return_value = self.evaluate_expression(return_expression.clone())?;
+                self.run_hooks(|hook, interpreter| {
+                    hook.after_statement(interpreter, &statement, &return_value);
+                    Ok(())
+                })?;
                 return_value.set_return();
                 break;
             }
-            return_value = self.execute_statement(statement.clone())?;
+            return_value = self.execute_statement(statement)?;
             if return_value.is_return() {
                 break;
             }
         }
-        self.variables = old_variables;
         Ok(return_value)
     }
 
     fn evaluate_expression(&mut self, expression: Expression) -> Result<Object, Error> {
-        self.match_expression(expression)
+        self.step(expression.position())?;
+        if self.hooks.is_empty() && self.limits.size.is_none() {
+            return self.match_expression(expression);
+        }
+
+        let value = self.match_expression(expression.clone())?;
+        if let Some(position) = expression.position() {
+            self.check_size(&value, position)?;
+        }
+        self.run_hooks(|hook, interpreter| {
+            hook.after_expression(interpreter, &expression, &value);
+            Ok(())
+        })?;
+        Ok(value)
     }
 
     fn evaluate_binary_expression(
         &mut self,
         binary_expression: BinaryExpression,
     ) -> Result<Object, Error> {
-        let left = self.match_expression(*binary_expression.left)?;
+        let left = self.evaluate_expression(*binary_expression.left)?;
 
-        let right = self.match_expression(*binary_expression.right)?;
+        let right = self.evaluate_expression(*binary_expression.right)?;
 
         match binary_expression.operator.ttype {
             TokenType::And => Ok(Object::Boolean(
@@ -217,7 +654,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -230,7 +668,8 @@ impl Interpreter {
                 }
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -239,7 +678,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -254,7 +694,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -267,7 +708,8 @@ impl Interpreter {
                 }
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -276,7 +718,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -291,7 +734,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -304,7 +748,8 @@ impl Interpreter {
                 }
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -313,7 +758,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -328,7 +774,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -341,7 +788,8 @@ impl Interpreter {
                 }
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -350,7 +798,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -365,7 +814,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -378,7 +828,8 @@ impl Interpreter {
                 }
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -387,7 +838,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -402,7 +854,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -411,7 +864,8 @@ impl Interpreter {
                 )),
 
                 (Object::String(..), Object::String(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `string` as it's operand",
                         binary_expression.operator.lexeme
@@ -420,7 +874,8 @@ impl Interpreter {
                 )),
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -429,7 +884,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -444,7 +900,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -453,7 +910,8 @@ impl Interpreter {
                 )),
 
                 (Object::String(..), Object::String(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `string` as it's operand",
                         binary_expression.operator.lexeme
@@ -462,7 +920,8 @@ impl Interpreter {
                 )),
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -471,7 +930,54 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
+                    format!(
+                        "Type mismatch, `{}` expects same type on both side",
+                        binary_expression.operator.lexeme
+                    ),
+                    binary_expression.operator.position,
+                )),
+            },
+
+            TokenType::StarStar => match (left, right) {
+                (Object::Number(x, ..), Object::Number(y, ..)) => {
+                    Ok(Object::Number(x.powf(y), Meta::default()))
+                }
+
+                (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
+                    format!(
+                        "Type mismatch, `{}` doesn't support `boolean` as it's operand",
+                        binary_expression.operator.lexeme
+                    ),
+                    binary_expression.operator.position,
+                )),
+
+                (Object::String(..), Object::String(..)) => Err(Error::new(
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
+                    format!(
+                        "Type mismatch, `{}` doesn't support `string` as it's operand",
+                        binary_expression.operator.lexeme
+                    ),
+                    binary_expression.operator.position,
+                )),
+
+                (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
+                    format!(
+                        "Type mismatch, `{}` doesn't support `nil` as it's operand",
+                        binary_expression.operator.lexeme
+                    ),
+                    binary_expression.operator.position,
+                )),
+
+                _ => Err(Error::new(
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -486,7 +992,8 @@ impl Interpreter {
                 }
 
                 (Object::Boolean(..), Object::Boolean(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `boolean` as it's operand",
                         binary_expression.operator.lexeme
@@ -495,7 +1002,8 @@ impl Interpreter {
                 )),
 
                 (Object::String(..), Object::String(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `string` as it's operand",
                         binary_expression.operator.lexeme
@@ -504,7 +1012,8 @@ impl Interpreter {
                 )),
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -513,7 +1022,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -528,7 +1038,8 @@ impl Interpreter {
                 }
 
                 (Object::String(..), Object::String(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `string` as it's operand",
                         binary_expression.operator.lexeme
@@ -537,7 +1048,8 @@ impl Interpreter {
                 )),
 
                 (Object::Nil(..), Object::Nil(..)) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` doesn't support `nil` as it's operand",
                         binary_expression.operator.lexeme
@@ -546,7 +1058,8 @@ impl Interpreter {
                 )),
 
                 _ => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::OPERAND_MISMATCH,
                     format!(
                         "Type mismatch, `{}` expects same type on both side",
                         binary_expression.operator.lexeme
@@ -557,6 +1070,7 @@ impl Interpreter {
 
             _ => Err(Error::new(
                 ErrorType::RuntimeError,
+                code::INVALID_OPERATOR,
                 format!(
                     "`{}` is not a binary operator.",
                     binary_expression.operator.lexeme
@@ -570,16 +1084,17 @@ impl Interpreter {
         &mut self,
         unary_expression: UnaryExpression,
     ) -> Result<Object, Error> {
-        let right = self.match_expression(*unary_expression.right)?;
+        let right = self.evaluate_expression(*unary_expression.right)?;
 
         match unary_expression.operator.ttype {
             TokenType::Not => Ok(Object::Boolean(!right.is_truthy(), Meta::default())),
 
             TokenType::Minus => match right {
-                Object::Number(x, ..) => Ok(Object::Number(x * -1., Meta::default())),
+                Object::Number(x, ..) => Ok(Object::Number(-x, Meta::default())),
 
                 Object::Boolean(..) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` does not support `boolean` as it's operand",
                         unary_expression.operator.lexeme
@@ -588,7 +1103,8 @@ impl Interpreter {
                 )),
 
                 Object::String(..) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` does not support `string` as it's operand",
                         unary_expression.operator.lexeme
@@ -597,7 +1113,8 @@ impl Interpreter {
                 )),
 
                 Object::Nil(..) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` does not support `nil` as it's operand",
                         unary_expression.operator.lexeme
@@ -606,17 +1123,29 @@ impl Interpreter {
                 )),
 
                 Object::Array(..) => Err(Error::new(
-                    ErrorType::RuntimeError,
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
                     format!(
                         "Type mismatch, `{}` does not support `array` as it's operand",
                         unary_expression.operator.lexeme
                     ),
                     unary_expression.operator.position,
                 )),
+
+                Object::Function(..) => Err(Error::new(
+                    ErrorType::TypeError,
+                    code::UNSUPPORTED_OPERAND,
+                    format!(
+                        "Type mismatch, `{}` does not support `function` as it's operand",
+                        unary_expression.operator.lexeme
+                    ),
+                    unary_expression.operator.position,
+                )),
             },
 
             _ => Err(Error::new(
                 ErrorType::RuntimeError,
+                code::INVALID_OPERATOR,
                 format!(
                     "`{}` is not a unary operator.",
                     unary_expression.operator.lexeme
@@ -638,17 +1167,147 @@ impl Interpreter {
         &mut self,
         call_expression: CallExpression,
     ) -> Result<Object, Error> {
-        let function_statement = self.functions.get(call_expression.identifier.clone())?;
+        let function_statement = match self.functions.get(call_expression.identifier.clone()) {
+            Ok(function_statement) => function_statement,
+            Err(err) => {
+                return match stdlib::find(&call_expression.identifier.lexeme) {
+                    Some(builtin) => self.call_builtin(builtin, call_expression),
+                    None => match self.variables.get(call_expression.identifier.clone()) {
+                        Ok(function) => {
+                            let mut arguments = Vec::new();
+                            for argument in call_expression.arguments {
+                                arguments.push(self.evaluate_expression(argument)?);
+                            }
+                            self.call(&function, arguments, &call_expression.identifier.position)
+                        }
+                        Err(..) => Err(err),
+                    },
+                }
+            }
+        };
         let paramiters = function_statement.paramiters.len();
         let arguments = call_expression.arguments.len();
         if paramiters != arguments {
-            return Err(Error::new(
-                ErrorType::RuntimeError,
+            Err(Error::new(
+                ErrorType::ArityError,
+                code::ARGUMENT_COUNT,
                 format!("Expected {} arguments, got {}", paramiters, arguments),
                 call_expression.identifier.position,
-            ));
+            ))
+        } else {
+            self.execute_function_statement(
+                call_expression.arguments,
+                function_statement,
+                call_expression.identifier.position,
+            )
+        }
+    }
+
+    fn call_builtin(
+        &mut self,
+        builtin: &Builtin,
+        call_expression: CallExpression,
+    ) -> Result<Object, Error> {
+        let position = call_expression.identifier.position;
+        builtin.check_arity(call_expression.arguments.len(), &position)?;
+        let mut expressions = call_expression.arguments;
+        let target = match expressions.first() {
+            Some(Expression::Identifier(identifier_expression)) if builtin.mutates() => {
+                identifier_expression.identifier.clone()
+            }
+            _ => {
+                let mut arguments = Vec::new();
+                for argument in expressions {
+                    arguments.push(self.evaluate_expression(argument)?);
+                }
+                // Changing an array that isn't stored anywhere would be lost.
+                if let (true, Some(Object::Array(..))) = (builtin.mutates(), arguments.first()) {
+                    return Err(Error::new(
+                        ErrorType::TypeError,
+                        code::ARGUMENT_TYPE,
+                        format!(
+                            "`{}` changes an array in place, it expects a variable",
+                            builtin.name
+                        ),
+                        position,
+                    ));
+                }
+                return (builtin.function)(self, &mut arguments, &position);
+            }
+        };
+        expressions.remove(0);
+
+        // The other arguments are evaluated first, they may read the variable.
+        let mut arguments = Vec::new();
+        for argument in expressions {
+            arguments.push(self.evaluate_expression(argument)?);
+        }
+        // The variable is moved into the arguments and back, so it changes without a copy.
+        // A function passed along, like the comparator of `sort`, may read the variable while
+        // the builtin runs, so then the variable keeps a copy of what it was until the end.
+        let variable = self.variables.get_mut(target.clone())?;
+        let value = if arguments
+            .iter()
+            .any(|argument| matches!(argument, Object::Function(..)))
+        {
+            variable.clone()
         } else {
-            self.execute_function_statement(call_expression.arguments, function_statement)
+            std::mem::replace(variable, Object::Nil(Meta::default()))
+        };
+        arguments.insert(0, value);
+        let result = (builtin.function)(self, &mut arguments, &position);
+        let value = std::mem::replace(&mut arguments[0], Object::Nil(Meta::default()));
+        let size = self.check_size(&value, &target.position);
+        *self.variables.get_mut(target)? = value;
+        size?;
+        result
+    }
+
+    // Calls a function value with arguments that are already evaluated, for builtins that
+    // take functions.
+    pub(crate) fn call(
+        &mut self,
+        function: &Object,
+        arguments: Vec<Object>,
+        position: &Position,
+    ) -> Result<Object, Error> {
+        let name = match function {
+            Object::Function(name, ..) => name,
+            object => {
+                return Err(Error::new(
+                    ErrorType::TypeError,
+                    code::NOT_A_FUNCTION,
+                    format!("`{}` is a `{}`, not a function", object, object.type_name()),
+                    position.clone(),
+                ))
+            }
+        };
+        let identifier = Token::new(TokenType::Identifier, name.clone(), None, position.clone());
+        match self.functions.get(identifier) {
+            Ok(function_statement) => {
+                if function_statement.paramiters.len() != arguments.len() {
+                    return Err(Error::new(
+                        ErrorType::ArityError,
+                        code::ARGUMENT_COUNT,
+                        format!(
+                            "`{}` expected {} arguments, got {}",
+                            name,
+                            function_statement.paramiters.len(),
+                            arguments.len()
+                        ),
+                        position.clone(),
+                    ));
+                }
+                self.call_function_statement(arguments, function_statement, position.clone())
+            }
+            Err(err) => match stdlib::find(name) {
+                Some(builtin) => {
+                    builtin.check_arity(arguments.len(), position)?;
+                    let mut arguments = arguments;
+                    (builtin.function)(self, &mut arguments, position)
+                }
+                None => Err(err),
+            },
         }
     }
 
@@ -656,7 +1315,15 @@ impl Interpreter {
         &self,
         identifier_expression: IdentifierExpression,
     ) -> Result<Object, Error> {
-        self.variables.get(identifier_expression.identifier)
+        let name = identifier_expression.identifier.lexeme.clone();
+        let function = self.functions.contains(&name) || stdlib::find(&name).is_some();
+        self.variables
+            .get(identifier_expression.identifier)
+            .or_else(|err| stdlib::constant(&name).ok_or(err))
+            .or_else(|err| match function {
+                true => Ok(Object::Function(name, Meta::default())),
+                false => Err(err),
+            })
     }
 
     fn match_expression(&mut self, expression: Expression) -> Result<Object, Error> {
@@ -0,0 +1,72 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// Bounds on what a program may consume, for running code that isn't trusted. Exceeding one
+// stops the program with a `RuntimeError`. Steps and time count from when the limits are
+// set, so a fresh interpreter per program gives each one the whole budget.
+#[derive(Debug, Default, Clone)]
+pub struct Limits {
+    // Statements and expressions evaluated.
+    pub steps: Option<u64>,
+    // Calls to user functions that haven't returned yet.
+    pub depth: Option<usize>,
+    // Bytes in a string or elements in an array.
+    pub size: Option<usize>,
+    // Wall-clock time.
+    pub time: Option<Duration>,
+}
+
+// Stops an interpreter from another thread or a signal handler. The program ends with an
+// `interrupted` error at its next statement, later programs run again.
+#[derive(Debug, Default, Clone)]
+pub struct InterruptHandle {
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    // Whether an interrupt was requested, clearing it.
+    pub(crate) fn take(&self) -> bool {
+        self.interrupted.swap(false, Ordering::SeqCst)
+    }
+}
+
+// What a program may reach outside the interpreter. Everything is allowed by default.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    // The `read` statement.
+    pub read: bool,
+    // Environment variables through `env`.
+    pub env: bool,
+    // Files on disk.
+    pub files: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            read: true,
+            env: true,
+            files: true,
+        }
+    }
+}
+
+impl Capabilities {
+    // Nothing outside the interpreter, `write` still works.
+    pub fn none() -> Self {
+        Self {
+            read: false,
+            env: false,
+            files: false,
+        }
+    }
+}
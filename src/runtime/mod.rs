@@ -1,2 +1,8 @@
+pub mod coverage;
 mod environment;
+pub mod hook;
 pub mod interpreter;
+pub mod limits;
+pub mod profiler;
+pub mod stdlib;
+pub mod tracer;
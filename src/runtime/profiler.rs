@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use crate::common::{ast::Statement, error::Error, position::Position};
+
+use super::{hook::Hook, interpreter::Interpreter};
+
+// What runs outside of any function.
+pub const MAIN: &str = "<main>";
+
+#[derive(Debug, Default, Clone)]
+pub struct FunctionProfile {
+    pub calls: usize,
+    // From call to return, counted once for recursive calls.
+    pub inclusive: Duration,
+    // Inclusive time minus the time spent in callees.
+    pub exclusive: Duration,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LineProfile {
+    pub hits: usize,
+    // The time spent on statements of the line, not counting calls they make.
+    pub time: Duration,
+}
+
+struct Call {
+    name: String,
+    // The names of the calls leading here, separated by `;` as flame graph tools expect.
+    stack: String,
+    start: Instant,
+    callees: Duration,
+    line: Option<(String, usize)>,
+}
+
+// Measures where a program spends its time. Time between two events (statements, calls and
+// returns) is charged to the line and call stack that were current at the first of them.
+pub struct Profiler {
+    calls: Vec<Call>,
+    last: Instant,
+    pub functions: HashMap<String, FunctionProfile>,
+    pub lines: HashMap<(String, usize), LineProfile>,
+    pub stacks: HashMap<String, Duration>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        let mut profiler = Self {
+            calls: Vec::new(),
+            last: Instant::now(),
+            functions: HashMap::new(),
+            lines: HashMap::new(),
+            stacks: HashMap::new(),
+        };
+        profiler.enter(MAIN);
+        profiler
+    }
+
+    // Closes the calls that are still open, like the ones an error unwound, and `<main>`.
+    pub fn finish(&mut self) {
+        while !self.calls.is_empty() {
+            self.exit();
+        }
+    }
+
+    fn charge(&mut self) {
+        let now = Instant::now();
+        let elapsed = now - self.last;
+        self.last = now;
+
+        if let Some(call) = self.calls.last() {
+            *self.stacks.entry(call.stack.clone()).or_default() += elapsed;
+            if let Some(line) = &call.line {
+                self.lines.entry(line.clone()).or_default().time += elapsed;
+            }
+        }
+    }
+
+    fn enter(&mut self, name: &str) {
+        self.charge();
+        let stack = match self.calls.last() {
+            Some(caller) => format!("{};{}", caller.stack, name),
+            None => name.to_string(),
+        };
+        self.calls.push(Call {
+            name: name.to_string(),
+            stack,
+            start: self.last,
+            callees: Duration::default(),
+            line: None,
+        });
+        self.functions.entry(name.to_string()).or_default().calls += 1;
+    }
+
+    fn exit(&mut self) {
+        self.charge();
+        let call = match self.calls.pop() {
+            Some(call) => call,
+            None => return,
+        };
+        let duration = self.last - call.start;
+        if let Some(caller) = self.calls.last_mut() {
+            caller.callees += duration;
+        }
+
+        let recursive = self.calls.iter().any(|caller| caller.name == call.name);
+        let function = self.functions.entry(call.name).or_default();
+        if !recursive {
+            function.inclusive += duration;
+        }
+        function.exclusive += duration.saturating_sub(call.callees);
+    }
+
+    // A table of functions by exclusive time, then of the lines that took longest.
+    pub fn summary(&self, lines: usize) -> String {
+        let mut summary = String::new();
+        let mut functions: Vec<(&String, &FunctionProfile)> = self.functions.iter().collect();
+        functions.sort_by(|a, b| b.1.exclusive.cmp(&a.1.exclusive).then(a.0.cmp(b.0)));
+        writeln!(
+            summary,
+            "{:<24} {:>8} {:>14} {:>14}",
+            "function", "calls", "inclusive", "exclusive"
+        )
+        .ok();
+        for (name, function) in functions {
+            writeln!(
+                summary,
+                "{:<24} {:>8} {:>14} {:>14}",
+                name,
+                function.calls,
+                milliseconds(function.inclusive),
+                milliseconds(function.exclusive)
+            )
+            .ok();
+        }
+
+        let mut rows: Vec<(&(String, usize), &LineProfile)> = self.lines.iter().collect();
+        rows.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(b.0)));
+        writeln!(summary, "\n{:<32} {:>8} {:>14}", "line", "hits", "time").ok();
+        for ((source_path, row), line) in rows.into_iter().take(lines) {
+            writeln!(
+                summary,
+                "{:<32} {:>8} {:>14}",
+                format!("{}:{}", source_path, row),
+                line.hits,
+                milliseconds(line.time)
+            )
+            .ok();
+        }
+        summary
+    }
+
+    // One `stack microseconds` line per call stack, the input flame graph tools take.
+    pub fn folded(&self) -> String {
+        let mut stacks: Vec<(&String, &Duration)> = self.stacks.iter().collect();
+        stacks.sort();
+        let mut folded = String::new();
+        for (stack, time) in stacks {
+            writeln!(folded, "{} {}", stack, time.as_micros()).ok();
+        }
+        folded
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl Hook for Profiler {
+    fn before_statement(
+        &mut self,
+        _interpreter: &mut Interpreter,
+        statement: &Statement,
+    ) -> Result<(), Error> {
+        self.charge();
+        if let (Some(position), Some(call)) = (statement.position(), self.calls.last_mut()) {
+            let line = (position.source_path.clone(), position.row);
+            self.lines.entry(line.clone()).or_default().hits += 1;
+            call.line = Some(line);
+        }
+        Ok(())
+    }
+
+    fn enter_function(&mut self, _interpreter: &mut Interpreter, name: &str, _position: &Position) {
+        self.enter(name);
+    }
+
+    fn exit_function(&mut self, _interpreter: &mut Interpreter, _name: &str) {
+        self.exit();
+    }
+}
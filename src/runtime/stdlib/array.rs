@@ -0,0 +1,433 @@
+use std::cmp::Ordering;
+
+use crate::common::{
+    error::{code, Error, ErrorType},
+    object::{Meta, Object},
+    position::Position,
+};
+
+use super::{
+    array_with_capacity, expect_array, expect_number, expect_whole, type_error, Builtin,
+    Interpreter,
+};
+
+// `insert`, `remove`, `reverse` and `sort` change the array variable they are given, the
+// others return a new array.
+pub(super) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "insert",
+        paramiters: &["&array", "index", "value"],
+        function: insert,
+    },
+    Builtin {
+        name: "remove",
+        paramiters: &["&array", "index"],
+        function: remove,
+    },
+    Builtin {
+        name: "reverse",
+        paramiters: &["&array"],
+        function: reverse,
+    },
+    Builtin {
+        name: "sort",
+        paramiters: &["&array", "compare?"],
+        function: sort,
+    },
+    Builtin {
+        name: "map",
+        paramiters: &["array", "function"],
+        function: map,
+    },
+    Builtin {
+        name: "filter",
+        paramiters: &["array", "predicate"],
+        function: filter,
+    },
+    Builtin {
+        name: "reduce",
+        paramiters: &["array", "function", "initial?"],
+        function: reduce,
+    },
+    Builtin {
+        name: "any",
+        paramiters: &["array", "predicate"],
+        function: any,
+    },
+    Builtin {
+        name: "all",
+        paramiters: &["array", "predicate"],
+        function: all,
+    },
+    Builtin {
+        name: "index_of",
+        paramiters: &["array", "value"],
+        function: index_of,
+    },
+    Builtin {
+        name: "zip",
+        paramiters: &["left", "right"],
+        function: zip,
+    },
+    Builtin {
+        name: "enumerate",
+        paramiters: &["array"],
+        function: enumerate,
+    },
+    Builtin {
+        name: "flatten",
+        paramiters: &["array"],
+        function: flatten,
+    },
+    Builtin {
+        name: "unique",
+        paramiters: &["array"],
+        function: unique,
+    },
+    Builtin {
+        name: "range",
+        paramiters: &["start", "end?", "step?"],
+        function: range,
+    },
+];
+
+fn array(values: Vec<Object>) -> Result<Object, Error> {
+    Ok(Object::Array(values, Meta::default()))
+}
+
+fn nil() -> Result<Object, Error> {
+    Ok(Object::Nil(Meta::default()))
+}
+
+fn expect_array_mut<'a>(
+    name: &str,
+    object: &'a mut Object,
+    position: &Position,
+) -> Result<&'a mut Vec<Object>, Error> {
+    match object {
+        Object::Array(array, ..) => Ok(array),
+        object => Err(type_error(name, "an `array`", object, position)),
+    }
+}
+
+// An index into an array of `len` elements, `len` itself only when `end` allows it.
+fn expect_index(
+    name: &str,
+    object: &Object,
+    len: usize,
+    end: bool,
+    position: &Position,
+) -> Result<usize, Error> {
+    let index = expect_whole(name, object, position)?;
+    let last = if end { len } else { len.wrapping_sub(1) };
+    if index < 0 || len == 0 && !end || index as usize > last {
+        return Err(Error::new(
+            ErrorType::IndexError,
+            code::INDEX_OUT_OF_RANGE,
+            format!(
+                "`{}` index {} is out of range for an array of length {}",
+                name, index, len
+            ),
+            position.clone(),
+        ));
+    }
+    Ok(index as usize)
+}
+
+fn insert(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let len = expect_array("insert", &arguments[0], position)?.len();
+    let index = expect_index("insert", &arguments[1], len, true, position)?;
+    interpreter.check_length("array", len + 1, position)?;
+    let value = std::mem::replace(&mut arguments[2], Object::Nil(Meta::default()));
+    expect_array_mut("insert", &mut arguments[0], position)?.insert(index, value);
+    nil()
+}
+
+// Returns the removed element.
+fn remove(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let len = expect_array("remove", &arguments[0], position)?.len();
+    let index = expect_index("remove", &arguments[1], len, false, position)?;
+    Ok(expect_array_mut("remove", &mut arguments[0], position)?.remove(index))
+}
+
+fn reverse(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    expect_array_mut("reverse", &mut arguments[0], position)?.reverse();
+    nil()
+}
+
+// Numbers and strings sort in their natural order. A comparator returns a negative number
+// when its first argument comes first, a positive one when it comes last and 0 otherwise.
+fn sort(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let (array, compare) = arguments.split_at_mut(1);
+    let array = expect_array_mut("sort", &mut array[0], position)?;
+    let mut error = None;
+    array.sort_by(|a, b| {
+        if error.is_some() {
+            return Ordering::Equal;
+        }
+        let ordering = match compare.first() {
+            Some(compare) => interpreter
+                .call(compare, vec![a.clone(), b.clone()], position)
+                .and_then(|order| expect_number("sort", &order, position))
+                .map(|order| order.partial_cmp(&0.).unwrap_or(Ordering::Equal)),
+            None => natural_order(a, b, position),
+        };
+        ordering.unwrap_or_else(|err| {
+            error = Some(err);
+            Ordering::Equal
+        })
+    });
+    match error {
+        Some(err) => Err(err),
+        None => nil(),
+    }
+}
+
+fn natural_order(a: &Object, b: &Object, position: &Position) -> Result<Ordering, Error> {
+    match (a, b) {
+        (Object::Number(a, ..), Object::Number(b, ..)) => {
+            Ok(a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        }
+        (Object::String(a, ..), Object::String(b, ..)) => Ok(a.cmp(b)),
+        (a, b) => Err(Error::new(
+            ErrorType::TypeError,
+            code::ARGUMENT_TYPE,
+            format!(
+                "`sort` can't order a `{}` and a `{}` without a comparator",
+                a.type_name(),
+                b.type_name()
+            ),
+            position.clone(),
+        )),
+    }
+}
+
+fn map(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut values = Vec::new();
+    for value in expect_array("map", &arguments[0], position)? {
+        values.push(interpreter.call(&arguments[1], vec![value.clone()], position)?);
+    }
+    array(values)
+}
+
+fn filter(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut values = Vec::new();
+    for value in expect_array("filter", &arguments[0], position)? {
+        if interpreter
+            .call(&arguments[1], vec![value.clone()], position)?
+            .is_truthy()
+        {
+            values.push(value.clone());
+        }
+    }
+    array(values)
+}
+
+// Folds the array into one value, starting from the first element without `initial`.
+fn reduce(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut values = expect_array("reduce", &arguments[0], position)?.iter();
+    let mut accumulator = match arguments.get(2).or_else(|| values.next()) {
+        Some(initial) => initial.clone(),
+        None => return nil(),
+    };
+    for value in values {
+        accumulator =
+            interpreter.call(&arguments[1], vec![accumulator, value.clone()], position)?;
+    }
+    Ok(accumulator)
+}
+
+fn any(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    for value in expect_array("any", &arguments[0], position)? {
+        if interpreter
+            .call(&arguments[1], vec![value.clone()], position)?
+            .is_truthy()
+        {
+            return Ok(Object::Boolean(true, Meta::default()));
+        }
+    }
+    Ok(Object::Boolean(false, Meta::default()))
+}
+
+fn all(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    for value in expect_array("all", &arguments[0], position)? {
+        if !interpreter
+            .call(&arguments[1], vec![value.clone()], position)?
+            .is_truthy()
+        {
+            return Ok(Object::Boolean(false, Meta::default()));
+        }
+    }
+    Ok(Object::Boolean(true, Meta::default()))
+}
+
+// The first element the predicate holds for, `nil` when there is none. `find` on a string
+// searches for a part of it instead.
+pub(super) fn find(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    for value in expect_array("find", &arguments[0], position)? {
+        if interpreter
+            .call(&arguments[1], vec![value.clone()], position)?
+            .is_truthy()
+        {
+            return Ok(value.clone());
+        }
+    }
+    nil()
+}
+
+fn index_of(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let values = expect_array("index_of", &arguments[0], position)?;
+    match values.iter().position(|value| *value == arguments[1]) {
+        Some(index) => Ok(Object::Number(index as f64, Meta::default())),
+        None => nil(),
+    }
+}
+
+// Pairs up the elements of both arrays, as long as the shorter one.
+fn zip(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let left = expect_array("zip", &arguments[0], position)?;
+    let right = expect_array("zip", &arguments[1], position)?;
+    let pairs = left
+        .iter()
+        .zip(right)
+        .map(|(left, right)| Object::Array(vec![left.clone(), right.clone()], Meta::default()))
+        .collect();
+    array(pairs)
+}
+
+fn enumerate(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let values = expect_array("enumerate", &arguments[0], position)?;
+    let pairs = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            Object::Array(
+                vec![Object::Number(index as f64, Meta::default()), value.clone()],
+                Meta::default(),
+            )
+        })
+        .collect();
+    array(pairs)
+}
+
+// Splices the elements of nested arrays in, one level deep.
+fn flatten(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut values = Vec::new();
+    for value in expect_array("flatten", &arguments[0], position)? {
+        match value {
+            Object::Array(nested, ..) => values.extend(nested.iter().cloned()),
+            value => values.push(value.clone()),
+        }
+    }
+    array(values)
+}
+
+// Keeps the first of equal elements, in order.
+fn unique(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut values: Vec<Object> = Vec::new();
+    for value in expect_array("unique", &arguments[0], position)? {
+        if !values.contains(value) {
+            values.push(value.clone());
+        }
+    }
+    array(values)
+}
+
+// `range(end)` counts from 0, `step` may be negative to count down.
+fn range(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let (start, end) = match arguments.get(1) {
+        Some(end) => (
+            expect_number("range", &arguments[0], position)?,
+            expect_number("range", end, position)?,
+        ),
+        None => (0., expect_number("range", &arguments[0], position)?),
+    };
+    let step = match arguments.get(2) {
+        Some(step) => expect_number("range", step, position)?,
+        None => 1.,
+    };
+    if step == 0. || !step.is_finite() {
+        return Err(Error::new(
+            ErrorType::TypeError,
+            code::ARGUMENT_TYPE,
+            format!("`range` expects a step other than 0, found `{}`", step),
+            position.clone(),
+        ));
+    }
+    let count = ((end - start) / step).ceil().max(0.);
+    let size = if count < usize::MAX as f64 {
+        Some(count as usize)
+    } else {
+        None
+    };
+    let mut values = array_with_capacity(interpreter, size, position)?;
+    values.extend(
+        (0..count as usize)
+            .map(|index| Object::Number(start + index as f64 * step, Meta::default())),
+    );
+    array(values)
+}
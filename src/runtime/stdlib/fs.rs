@@ -0,0 +1,213 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+};
+
+use crate::common::{
+    error::{code, Error, ErrorType},
+    object::{Meta, Object},
+    position::Position,
+};
+
+use super::{expect_string, Builtin, Interpreter};
+
+// Every one of these fails with a `NOT_ALLOWED` error when the interpreter's capabilities
+// deny file access.
+pub(super) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "read_file",
+        paramiters: &["path"],
+        function: read_file,
+    },
+    Builtin {
+        name: "write_file",
+        paramiters: &["path", "content"],
+        function: write_file,
+    },
+    Builtin {
+        name: "append_file",
+        paramiters: &["path", "content"],
+        function: append_file,
+    },
+    Builtin {
+        name: "read_lines",
+        paramiters: &["path"],
+        function: read_lines,
+    },
+    Builtin {
+        name: "exists",
+        paramiters: &["path"],
+        function: exists,
+    },
+    Builtin {
+        name: "list_dir",
+        paramiters: &["path"],
+        function: list_dir,
+    },
+    Builtin {
+        name: "mkdir",
+        paramiters: &["path"],
+        function: mkdir,
+    },
+    Builtin {
+        name: "remove_file",
+        paramiters: &["path"],
+        function: remove_file,
+    },
+    Builtin {
+        name: "rename",
+        paramiters: &["from", "to"],
+        function: rename,
+    },
+];
+
+fn nil() -> Result<Object, Error> {
+    Ok(Object::Nil(Meta::default()))
+}
+
+fn io_error(action: &str, path: &str, err: io::Error, position: &Position) -> Error {
+    Error::new(
+        ErrorType::IoError,
+        code::IO_FAILURE,
+        format!("Could not {} `{}`: {}", action, path, err),
+        position.clone(),
+    )
+}
+
+// The path argument of a builtin, once the interpreter allows file access.
+fn expect_path<'a>(
+    interpreter: &Interpreter,
+    name: &str,
+    object: &'a Object,
+    position: &Position,
+) -> Result<&'a str, Error> {
+    interpreter.require(|capabilities| capabilities.files, "File access", position)?;
+    expect_string(name, object, position)
+}
+
+// Reads a whole file, refusing files larger than the size limit before reading them.
+fn read(interpreter: &Interpreter, path: &str, position: &Position) -> Result<String, Error> {
+    let size = fs::metadata(path)
+        .map_err(|err| io_error("read", path, err, position))?
+        .len();
+    interpreter.check_length("string", size as usize, position)?;
+    fs::read_to_string(path).map_err(|err| io_error("read", path, err, position))
+}
+
+fn read_file(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "read_file", &arguments[0], position)?;
+    Ok(Object::String(
+        read(interpreter, path, position)?,
+        Meta::default(),
+    ))
+}
+
+// Writes the content as `write` prints it.
+fn write_file(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "write_file", &arguments[0], position)?;
+    fs::write(path, arguments[1].to_string())
+        .map_err(|err| io_error("write", path, err, position))?;
+    nil()
+}
+
+fn append_file(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "append_file", &arguments[0], position)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| write!(file, "{}", arguments[1]))
+        .map_err(|err| io_error("append to", path, err, position))?;
+    nil()
+}
+
+fn read_lines(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "read_lines", &arguments[0], position)?;
+    let lines = read(interpreter, path, position)?
+        .lines()
+        .map(|line| Object::String(line.to_string(), Meta::default()))
+        .collect();
+    Ok(Object::Array(lines, Meta::default()))
+}
+
+fn exists(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "exists", &arguments[0], position)?;
+    Ok(Object::Boolean(fs::metadata(path).is_ok(), Meta::default()))
+}
+
+// The names of the entries in a directory, sorted.
+fn list_dir(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "list_dir", &arguments[0], position)?;
+    let mut names = Vec::new();
+    for entry in fs::read_dir(path).map_err(|err| io_error("list", path, err, position))? {
+        let entry = entry.map_err(|err| io_error("list", path, err, position))?;
+        names.push(entry.file_name().to_string_lossy().to_string());
+    }
+    names.sort();
+    let names = names
+        .into_iter()
+        .map(|name| Object::String(name, Meta::default()))
+        .collect();
+    Ok(Object::Array(names, Meta::default()))
+}
+
+// Creates the directory and any missing parents.
+fn mkdir(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "mkdir", &arguments[0], position)?;
+    fs::create_dir_all(path).map_err(|err| io_error("create", path, err, position))?;
+    nil()
+}
+
+// Removes a file or an empty directory.
+fn remove_file(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let path = expect_path(interpreter, "remove_file", &arguments[0], position)?;
+    let removed = match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir(path),
+        _ => fs::remove_file(path),
+    };
+    removed.map_err(|err| io_error("remove", path, err, position))?;
+    nil()
+}
+
+fn rename(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let from = expect_path(interpreter, "rename", &arguments[0], position)?;
+    let to = expect_string("rename", &arguments[1], position)?;
+    fs::rename(from, to).map_err(|err| io_error("rename", from, err, position))?;
+    nil()
+}
@@ -0,0 +1,291 @@
+use std::f64::consts;
+
+use crate::common::{
+    error::{code, Error, ErrorType},
+    object::{Meta, Object},
+    position::Position,
+};
+
+use super::{expect_number, expect_string, expect_whole, Builtin, Interpreter};
+
+pub(super) const CONSTANTS: &[(&str, f64)] = &[("pi", consts::PI), ("e", consts::E)];
+
+pub(super) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "abs",
+        paramiters: &["x"],
+        function: abs,
+    },
+    Builtin {
+        name: "floor",
+        paramiters: &["x"],
+        function: floor,
+    },
+    Builtin {
+        name: "ceil",
+        paramiters: &["x"],
+        function: ceil,
+    },
+    Builtin {
+        name: "round",
+        paramiters: &["x"],
+        function: round,
+    },
+    Builtin {
+        name: "sqrt",
+        paramiters: &["x"],
+        function: sqrt,
+    },
+    Builtin {
+        name: "pow",
+        paramiters: &["base", "exponent"],
+        function: pow,
+    },
+    Builtin {
+        name: "min",
+        paramiters: &["x", "xs..."],
+        function: min,
+    },
+    Builtin {
+        name: "max",
+        paramiters: &["x", "xs..."],
+        function: max,
+    },
+    Builtin {
+        name: "clamp",
+        paramiters: &["x", "low", "high"],
+        function: clamp,
+    },
+    Builtin {
+        name: "sin",
+        paramiters: &["x"],
+        function: sin,
+    },
+    Builtin {
+        name: "cos",
+        paramiters: &["x"],
+        function: cos,
+    },
+    Builtin {
+        name: "tan",
+        paramiters: &["x"],
+        function: tan,
+    },
+    Builtin {
+        name: "log",
+        paramiters: &["x", "base?"],
+        function: log,
+    },
+    Builtin {
+        name: "exp",
+        paramiters: &["x"],
+        function: exp,
+    },
+    Builtin {
+        name: "parse_int",
+        paramiters: &["string", "radix?"],
+        function: parse_int,
+    },
+    Builtin {
+        name: "format_int",
+        paramiters: &["x", "radix?"],
+        function: format_int,
+    },
+];
+
+fn number(value: f64) -> Result<Object, Error> {
+    Ok(Object::Number(value, Meta::default()))
+}
+
+fn abs(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("abs", &arguments[0], position)?.abs())
+}
+
+fn floor(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("floor", &arguments[0], position)?.floor())
+}
+
+fn ceil(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("ceil", &arguments[0], position)?.ceil())
+}
+
+fn round(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("round", &arguments[0], position)?.round())
+}
+
+fn sqrt(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("sqrt", &arguments[0], position)?.sqrt())
+}
+
+fn pow(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let base = expect_number("pow", &arguments[0], position)?;
+    let exponent = expect_number("pow", &arguments[1], position)?;
+    number(base.powf(exponent))
+}
+
+fn min(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut min = f64::INFINITY;
+    for argument in arguments.iter() {
+        min = min.min(expect_number("min", argument, position)?);
+    }
+    number(min)
+}
+
+fn max(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let mut max = f64::NEG_INFINITY;
+    for argument in arguments.iter() {
+        max = max.max(expect_number("max", argument, position)?);
+    }
+    number(max)
+}
+
+fn clamp(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let x = expect_number("clamp", &arguments[0], position)?;
+    let low = expect_number("clamp", &arguments[1], position)?;
+    let high = expect_number("clamp", &arguments[2], position)?;
+    if low > high {
+        return Err(Error::new(
+            ErrorType::TypeError,
+            code::ARGUMENT_TYPE,
+            format!("`clamp` expects `low` <= `high`, found {} > {}", low, high),
+            position.clone(),
+        ));
+    }
+    number(x.max(low).min(high))
+}
+
+fn sin(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("sin", &arguments[0], position)?.sin())
+}
+
+fn cos(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("cos", &arguments[0], position)?.cos())
+}
+
+fn tan(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("tan", &arguments[0], position)?.tan())
+}
+
+// The natural logarithm unless a base is given.
+fn log(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let x = expect_number("log", &arguments[0], position)?;
+    match arguments.get(1) {
+        Some(base) => number(x.log(expect_number("log", base, position)?)),
+        None => number(x.ln()),
+    }
+}
+
+fn exp(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    number(expect_number("exp", &arguments[0], position)?.exp())
+}
+
+fn expect_radix(name: &str, object: Option<&Object>, position: &Position) -> Result<u32, Error> {
+    let radix = match object {
+        Some(object) => expect_whole(name, object, position)?,
+        None => return Ok(10),
+    };
+    if !(2..=36).contains(&radix) {
+        return Err(Error::new(
+            ErrorType::TypeError,
+            code::ARGUMENT_TYPE,
+            format!("`{}` expects a radix from 2 to 36, found `{}`", name, radix),
+            position.clone(),
+        ));
+    }
+    Ok(radix as u32)
+}
+
+// `nil` when the string isn't a whole number in the radix.
+fn parse_int(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let string = expect_string("parse_int", &arguments[0], position)?;
+    let radix = expect_radix("parse_int", arguments.get(1), position)?;
+    match i64::from_str_radix(string.trim(), radix) {
+        Ok(value) => number(value as f64),
+        Err(..) => Ok(Object::Nil(Meta::default())),
+    }
+}
+
+fn format_int(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_whole("format_int", &arguments[0], position)?;
+    let radix = expect_radix("format_int", arguments.get(1), position)?;
+    let mut digits = Vec::new();
+    let mut rest = value.unsigned_abs();
+    loop {
+        digits.push(std::char::from_digit((rest % radix as u64) as u32, radix).unwrap_or('0'));
+        rest /= radix as u64;
+        if rest == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        digits.push('-');
+    }
+    Ok(Object::String(
+        digits.into_iter().rev().collect(),
+        Meta::default(),
+    ))
+}
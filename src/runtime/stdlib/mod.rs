@@ -0,0 +1,200 @@
+mod array;
+mod fs;
+mod math;
+mod string;
+mod system;
+
+use crate::common::{
+    error::{code, Error, ErrorType},
+    object::{Meta, Object},
+    position::Position,
+};
+
+use super::interpreter::Interpreter;
+
+pub(crate) type Native = fn(&mut Interpreter, &mut [Object], &Position) -> Result<Object, Error>;
+
+// A function implemented by the interpreter itself. Paramiters ending with `?` are optional
+// and a paramiter ending with `...` takes any number of arguments. A first paramiter starting
+// with `&` takes a variable, which the function changes in place.
+pub struct Builtin {
+    pub name: &'static str,
+    pub paramiters: &'static [&'static str],
+    pub(crate) function: Native,
+}
+
+impl Builtin {
+    pub fn arity(&self) -> (usize, Option<usize>) {
+        let required = self
+            .paramiters
+            .iter()
+            .filter(|paramiter| !paramiter.ends_with('?') && !paramiter.ends_with("..."))
+            .count();
+        if self
+            .paramiters
+            .iter()
+            .any(|paramiter| paramiter.ends_with("..."))
+        {
+            (required, None)
+        } else {
+            (required, Some(self.paramiters.len()))
+        }
+    }
+
+    pub fn mutates(&self) -> bool {
+        matches!(self.paramiters.first(), Some(paramiter) if paramiter.starts_with('&'))
+    }
+
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.paramiters.join(", "))
+    }
+
+    pub(crate) fn check_arity(&self, arguments: usize, position: &Position) -> Result<(), Error> {
+        let expected = match self.arity() {
+            (min, Some(max)) if arguments >= min && arguments <= max => return Ok(()),
+            (min, None) if arguments >= min => return Ok(()),
+            (min, Some(max)) if min == max => format!("{}", min),
+            (min, Some(max)) => format!("{} to {}", min, max),
+            (min, None) => format!("at least {}", min),
+        };
+        Err(Error::new(
+            ErrorType::ArityError,
+            code::ARGUMENT_COUNT,
+            format!(
+                "`{}` expected {} arguments, got {}",
+                self.signature(),
+                expected,
+                arguments
+            ),
+            position.clone(),
+        ))
+    }
+}
+
+const MODULES: &[&[Builtin]] = &[
+    system::BUILTINS,
+    math::BUILTINS,
+    string::BUILTINS,
+    array::BUILTINS,
+    fs::BUILTINS,
+];
+
+pub fn builtins() -> impl Iterator<Item = &'static Builtin> {
+    MODULES.iter().flat_map(|module| module.iter())
+}
+
+// Names that read as a value unless the program declares a variable of the same name.
+pub fn constants() -> impl Iterator<Item = (&'static str, Object)> {
+    math::CONSTANTS
+        .iter()
+        .map(|(name, value)| (*name, Object::Number(*value, Meta::default())))
+}
+
+pub fn constant(name: &str) -> Option<Object> {
+    constants()
+        .find(|(constant, _)| *constant == name)
+        .map(|(_, value)| value)
+}
+
+pub fn find(name: &str) -> Option<&'static Builtin> {
+    builtins().find(|builtin| builtin.name == name)
+}
+
+pub(crate) fn type_error(name: &str, expected: &str, found: &Object, position: &Position) -> Error {
+    Error::new(
+        ErrorType::TypeError,
+        code::ARGUMENT_TYPE,
+        format!(
+            "`{}` expects {} as it's argument, found `{}`",
+            name,
+            expected,
+            found.type_name()
+        ),
+        position.clone(),
+    )
+}
+
+pub(crate) fn expect_number(
+    name: &str,
+    object: &Object,
+    position: &Position,
+) -> Result<f64, Error> {
+    match object {
+        Object::Number(number, ..) => Ok(*number),
+        object => Err(type_error(name, "a `number`", object, position)),
+    }
+}
+
+pub(crate) fn expect_string<'a>(
+    name: &str,
+    object: &'a Object,
+    position: &Position,
+) -> Result<&'a str, Error> {
+    match object {
+        Object::String(string, ..) => Ok(string),
+        object => Err(type_error(name, "a `string`", object, position)),
+    }
+}
+
+pub(crate) fn expect_whole(name: &str, object: &Object, position: &Position) -> Result<i64, Error> {
+    let number = expect_number(name, object, position)?;
+    if number.fract() != 0. || number.abs() > i64::MAX as f64 {
+        return Err(Error::new(
+            ErrorType::TypeError,
+            code::ARGUMENT_TYPE,
+            format!("`{}` expects a whole number, found `{}`", name, number),
+            position.clone(),
+        ));
+    }
+    Ok(number as i64)
+}
+
+pub(crate) fn expect_array<'a>(
+    name: &str,
+    object: &'a Object,
+    position: &Position,
+) -> Result<&'a [Object], Error> {
+    match object {
+        Object::Array(array, ..) => Ok(array),
+        object => Err(type_error(name, "an `array`", object, position)),
+    }
+}
+
+// Makes room for a string of `size` bytes up front, `None` meaning the size overflowed, so a
+// string too large for the limits or the machine is an error instead of an abort.
+pub(crate) fn string_with_capacity(
+    interpreter: &Interpreter,
+    size: Option<usize>,
+    position: &Position,
+) -> Result<String, Error> {
+    let size = size.ok_or_else(|| too_large("string", position))?;
+    interpreter.check_length("string", size, position)?;
+    let mut string = String::new();
+    string
+        .try_reserve_exact(size)
+        .map_err(|_| too_large("string", position))?;
+    Ok(string)
+}
+
+pub(crate) fn array_with_capacity(
+    interpreter: &Interpreter,
+    size: Option<usize>,
+    position: &Position,
+) -> Result<Vec<Object>, Error> {
+    let size = size.ok_or_else(|| too_large("array", position))?;
+    interpreter.check_length("array", size, position)?;
+    let mut array = Vec::new();
+    array
+        .try_reserve_exact(size)
+        .map_err(|_| too_large("array", position))?;
+    Ok(array)
+}
+
+fn too_large(type_name: &str, position: &Position) -> Error {
+    Error::new(
+        ErrorType::RuntimeError,
+        code::SIZE_LIMIT,
+        format!("The `{}` is too large to allocate", type_name),
+        position.clone(),
+    )
+}
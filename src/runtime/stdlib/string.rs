@@ -0,0 +1,407 @@
+use crate::common::{
+    error::{code, Error, ErrorType},
+    object::{Meta, Object},
+    position::Position,
+};
+
+use super::{
+    expect_array, expect_string, expect_whole, string_with_capacity, type_error, Builtin,
+    Interpreter,
+};
+
+// Strings are indexed and measured in Unicode scalar values, not bytes.
+pub(super) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "len",
+        paramiters: &["value"],
+        function: len,
+    },
+    Builtin {
+        name: "upper",
+        paramiters: &["string"],
+        function: upper,
+    },
+    Builtin {
+        name: "lower",
+        paramiters: &["string"],
+        function: lower,
+    },
+    Builtin {
+        name: "trim",
+        paramiters: &["string"],
+        function: trim,
+    },
+    Builtin {
+        name: "split",
+        paramiters: &["string", "separator?"],
+        function: split,
+    },
+    Builtin {
+        name: "join",
+        paramiters: &["array", "separator?"],
+        function: join,
+    },
+    Builtin {
+        name: "replace",
+        paramiters: &["string", "from", "to"],
+        function: replace,
+    },
+    Builtin {
+        name: "contains",
+        paramiters: &["string", "part"],
+        function: contains,
+    },
+    Builtin {
+        name: "starts_with",
+        paramiters: &["string", "prefix"],
+        function: starts_with,
+    },
+    Builtin {
+        name: "ends_with",
+        paramiters: &["string", "suffix"],
+        function: ends_with,
+    },
+    Builtin {
+        name: "find",
+        paramiters: &["value", "part"],
+        function: find,
+    },
+    Builtin {
+        name: "repeat",
+        paramiters: &["string", "count"],
+        function: repeat,
+    },
+    Builtin {
+        name: "chars",
+        paramiters: &["string"],
+        function: chars,
+    },
+    Builtin {
+        name: "substring",
+        paramiters: &["string", "start", "end?"],
+        function: substring,
+    },
+    Builtin {
+        name: "to_number",
+        paramiters: &["string"],
+        function: to_number,
+    },
+    Builtin {
+        name: "to_string",
+        paramiters: &["value"],
+        function: to_string,
+    },
+    Builtin {
+        name: "pad_left",
+        paramiters: &["string", "width", "fill?"],
+        function: pad_left,
+    },
+    Builtin {
+        name: "pad_right",
+        paramiters: &["string", "width", "fill?"],
+        function: pad_right,
+    },
+];
+
+fn string(value: String) -> Result<Object, Error> {
+    Ok(Object::String(value, Meta::default()))
+}
+
+fn boolean(value: bool) -> Result<Object, Error> {
+    Ok(Object::Boolean(value, Meta::default()))
+}
+
+fn strings<'a>(parts: impl Iterator<Item = &'a str>) -> Result<Object, Error> {
+    let parts = parts
+        .map(|part| Object::String(part.to_string(), Meta::default()))
+        .collect();
+    Ok(Object::Array(parts, Meta::default()))
+}
+
+// What `write` prints for a value, strings as they are.
+fn text(value: &Object) -> String {
+    match value {
+        Object::String(string, ..) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn expect_count(name: &str, object: &Object, position: &Position) -> Result<usize, Error> {
+    let count = expect_whole(name, object, position)?;
+    if count < 0 {
+        return Err(Error::new(
+            ErrorType::TypeError,
+            code::ARGUMENT_TYPE,
+            format!("`{}` expects a number >= 0, found `{}`", name, count),
+            position.clone(),
+        ));
+    }
+    Ok(count as usize)
+}
+
+fn len(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let len = match &arguments[0] {
+        Object::String(string, ..) => string.chars().count(),
+        Object::Array(array, ..) => array.len(),
+        object => {
+            return Err(type_error(
+                "len",
+                "a `string` or an `array`",
+                object,
+                position,
+            ))
+        }
+    };
+    Ok(Object::Number(len as f64, Meta::default()))
+}
+
+fn upper(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    string(expect_string("upper", &arguments[0], position)?.to_uppercase())
+}
+
+fn lower(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    string(expect_string("lower", &arguments[0], position)?.to_lowercase())
+}
+
+fn trim(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    string(
+        expect_string("trim", &arguments[0], position)?
+            .trim()
+            .to_string(),
+    )
+}
+
+// Splits on whitespace without a separator and into characters with an empty one.
+fn split(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("split", &arguments[0], position)?;
+    match arguments.get(1) {
+        None => strings(value.split_whitespace()),
+        Some(separator) => match expect_string("split", separator, position)? {
+            "" => strings(value.split("").filter(|part| !part.is_empty())),
+            separator => strings(value.split(separator)),
+        },
+    }
+}
+
+fn join(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let array = expect_array("join", &arguments[0], position)?;
+    let separator = match arguments.get(1) {
+        Some(separator) => expect_string("join", separator, position)?,
+        None => "",
+    };
+    let parts: Vec<String> = array.iter().map(text).collect();
+    string(parts.join(separator))
+}
+
+fn replace(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("replace", &arguments[0], position)?;
+    let from = expect_string("replace", &arguments[1], position)?;
+    let to = expect_string("replace", &arguments[2], position)?;
+    if from.is_empty() {
+        return string(value.to_string());
+    }
+    string(value.replace(from, to))
+}
+
+fn contains(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("contains", &arguments[0], position)?;
+    boolean(value.contains(expect_string("contains", &arguments[1], position)?))
+}
+
+fn starts_with(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("starts_with", &arguments[0], position)?;
+    boolean(value.starts_with(expect_string("starts_with", &arguments[1], position)?))
+}
+
+fn ends_with(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("ends_with", &arguments[0], position)?;
+    boolean(value.ends_with(expect_string("ends_with", &arguments[1], position)?))
+}
+
+// The index of the first character of `part`, `nil` when it doesn't occur. Arrays are
+// searched with a predicate instead.
+fn find(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    if let Object::Array(..) = arguments[0] {
+        return super::array::find(interpreter, arguments, position);
+    }
+    let value = expect_string("find", &arguments[0], position)?;
+    let part = expect_string("find", &arguments[1], position)?;
+    Ok(match value.find(part) {
+        Some(byte) => Object::Number(value[..byte].chars().count() as f64, Meta::default()),
+        None => Object::Nil(Meta::default()),
+    })
+}
+
+fn repeat(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("repeat", &arguments[0], position)?;
+    let count = expect_count("repeat", &arguments[1], position)?;
+    let mut repeated = string_with_capacity(interpreter, value.len().checked_mul(count), position)?;
+    for _ in 0..count {
+        repeated.push_str(value);
+    }
+    string(repeated)
+}
+
+fn chars(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("chars", &arguments[0], position)?;
+    let chars = value
+        .chars()
+        .map(|character| Object::String(character.to_string(), Meta::default()))
+        .collect();
+    Ok(Object::Array(chars, Meta::default()))
+}
+
+// The characters from `start` up to, not including, `end` (default the end of the string).
+fn substring(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("substring", &arguments[0], position)?;
+    let len = value.chars().count();
+    let start = expect_count("substring", &arguments[1], position)?;
+    let end = match arguments.get(2) {
+        Some(end) => expect_count("substring", end, position)?,
+        None => len,
+    };
+    if start > end || end > len {
+        return Err(Error::new(
+            ErrorType::IndexError,
+            code::INDEX_OUT_OF_RANGE,
+            format!(
+                "`substring` range {}..{} is out of range for a string of length {}",
+                start, end, len
+            ),
+            position.clone(),
+        ));
+    }
+    string(value.chars().skip(start).take(end - start).collect())
+}
+
+// `nil` when the string isn't a number.
+fn to_number(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let value = expect_string("to_number", &arguments[0], position)?;
+    Ok(match value.trim().parse::<f64>() {
+        Ok(number) if number.is_finite() => Object::Number(number, Meta::default()),
+        _ => Object::Nil(Meta::default()),
+    })
+}
+
+fn to_string(_: &mut Interpreter, arguments: &mut [Object], _: &Position) -> Result<Object, Error> {
+    string(text(&arguments[0]))
+}
+
+fn pad(
+    interpreter: &Interpreter,
+    name: &str,
+    arguments: &[Object],
+    position: &Position,
+) -> Result<(String, String), Error> {
+    let value = expect_string(name, &arguments[0], position)?;
+    let width = expect_count(name, &arguments[1], position)?;
+    let fill = match arguments.get(2) {
+        Some(fill) => {
+            let fill = expect_string(name, fill, position)?;
+            let mut chars = fill.chars();
+            match (chars.next(), chars.next()) {
+                (Some(character), None) => character,
+                _ => {
+                    return Err(Error::new(
+                        ErrorType::TypeError,
+                        code::ARGUMENT_TYPE,
+                        format!(
+                            "`{}` expects a single character to fill with, found `{}`",
+                            name, fill
+                        ),
+                        position.clone(),
+                    ))
+                }
+            }
+        }
+        None => ' ',
+    };
+    let padding = width.saturating_sub(value.chars().count());
+    let size = padding
+        .checked_mul(fill.len_utf8())
+        .and_then(|size| size.checked_add(value.len()));
+    let mut filling = string_with_capacity(interpreter, size, position)?;
+    for _ in 0..padding {
+        filling.push(fill);
+    }
+    Ok((value.to_string(), filling))
+}
+
+// Fills the string up to `width` characters on the left, with spaces unless `fill` is given.
+fn pad_left(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let (value, padding) = pad(interpreter, "pad_left", arguments, position)?;
+    string(padding + &value)
+}
+
+fn pad_right(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let (value, padding) = pad(interpreter, "pad_right", arguments, position)?;
+    string(value + &padding)
+}
@@ -0,0 +1,105 @@
+use std::env;
+
+use crate::common::{
+    error::{code, Error, ErrorType},
+    object::{Meta, Object},
+    position::Position,
+};
+
+use super::{expect_number, expect_string, Builtin, Interpreter};
+
+pub(super) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "env",
+        paramiters: &["name"],
+        function: env,
+    },
+    Builtin {
+        name: "exit",
+        paramiters: &["code?"],
+        function: exit,
+    },
+    Builtin {
+        name: "try",
+        paramiters: &["function", "handler", "arguments..."],
+        function: try_call,
+    },
+];
+
+fn env(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    interpreter.require(|capabilities| capabilities.env, "`env`", position)?;
+    let name = expect_string("env", &arguments[0], position)?;
+    match env::var(name) {
+        Ok(value) => Ok(Object::String(value, Meta::default())),
+        Err(..) => Ok(Object::Nil(Meta::default())),
+    }
+}
+
+// Calls `function` with the arguments and returns what it returns. If it fails, returns
+// what `handler` returns for the error message instead. `exit`, interrupts and the limits
+// of the interpreter can't be caught, so a sandboxed program still stops.
+fn try_call(
+    interpreter: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let function = arguments[0].clone();
+    let handler = arguments[1].clone();
+    let err = match interpreter.call(&function, arguments[2..].to_vec(), position) {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+    };
+    let uncatchable = [
+        code::EXIT,
+        code::STEP_LIMIT,
+        code::DEPTH_LIMIT,
+        code::SIZE_LIMIT,
+        code::TIME_LIMIT,
+        code::INTERRUPTED,
+    ];
+    if uncatchable.contains(&err.code()) {
+        return Err(err);
+    }
+    let message = Object::String(err.message().to_string(), Meta::default());
+    interpreter.call(&handler, vec![message], position)
+}
+
+// Statuses 2 to 5 are what `func` itself exits with on usage, lexing, parsing and runtime
+// errors, so a script can't use them and be mistaken for one.
+fn exit(
+    _: &mut Interpreter,
+    arguments: &mut [Object],
+    position: &Position,
+) -> Result<Object, Error> {
+    let status = match arguments.first() {
+        Some(object) => {
+            let status = expect_number("exit", object, position)?;
+            if status.fract() != 0.
+                || !(0. ..=255.).contains(&status)
+                || (2. ..=5.).contains(&status)
+            {
+                return Err(Error::new(
+                    ErrorType::TypeError,
+                    code::ARGUMENT_TYPE,
+                    format!(
+                        "`exit` expects 0, 1 or a whole number from 6 to 255, found `{}`",
+                        status
+                    ),
+                    position.clone(),
+                ));
+            }
+            status as i32
+        }
+        None => 0,
+    };
+    Err(Error::new(
+        ErrorType::Exit(status),
+        code::EXIT,
+        format!("Exited with status {}", status),
+        position.clone(),
+    ))
+}
@@ -0,0 +1,168 @@
+use std::{collections::HashMap, io::Write};
+
+use crate::common::{
+    ast::{Expression, Statement},
+    object::Object,
+    position::Position,
+};
+
+use super::{hook::Hook, interpreter::Interpreter};
+
+// Logs every statement with the value it produced, and every call with its arguments and
+// result, indented by call depth. Statements are logged once they ran, so the statements
+// inside a block come before the one holding the block.
+pub struct Tracer {
+    output: Box<dyn Write>,
+    functions: Vec<String>,
+    sources: HashMap<String, Vec<String>>,
+}
+
+impl Tracer {
+    pub fn new(output: impl Write + 'static) -> Self {
+        Self {
+            output: Box::new(output),
+            functions: Vec::new(),
+            sources: HashMap::new(),
+        }
+    }
+
+    // Only logs what runs directly inside these functions (`<main>` being the top level)
+    // and calls to them.
+    pub fn with_functions(mut self, functions: Vec<String>) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    // Logs statements of `source_path` with their source line instead of a summary.
+    pub fn with_source(mut self, source_path: &str, source: &str) -> Self {
+        self.sources.insert(
+            source_path.to_string(),
+            source.lines().map(str::to_string).collect(),
+        );
+        self
+    }
+
+    fn traces(&self, name: &str) -> bool {
+        self.functions.is_empty() || self.functions.iter().any(|function| function == name)
+    }
+
+    fn traces_frame(&self, interpreter: &Interpreter) -> bool {
+        let name = interpreter
+            .frames()
+            .last()
+            .map_or("<main>", |frame| frame.name.as_str());
+        self.traces(name)
+    }
+
+    fn log(&mut self, position: &Position, depth: usize, message: &str) {
+        writeln!(
+            self.output,
+            "{}:{}: {}{}",
+            position.source_path,
+            position.row,
+            "  ".repeat(depth),
+            message
+        )
+        .ok();
+    }
+
+    fn describe(&self, statement: &Statement, position: &Position) -> String {
+        if let Some(line) = self
+            .sources
+            .get(&position.source_path)
+            .and_then(|lines| lines.get(position.row.wrapping_sub(1)))
+        {
+            return line.trim().to_string();
+        }
+        match statement {
+            Statement::Let(let_statement) => {
+                format!("let {} = ...", let_statement.identifier.lexeme)
+            }
+            Statement::Assignment(assignment_statement) => {
+                format!("{} = ...", assignment_statement.identifier.lexeme)
+            }
+            Statement::Function(function_statement) => {
+                format!("func {}", function_statement.identifier.lexeme)
+            }
+            Statement::Test(test_statement) => format!("test {}", test_statement.name.lexeme),
+            Statement::BuiltinFunction(builtin_function_statement) => {
+                format!("{}(...)", builtin_function_statement.keyword.lexeme)
+            }
+            Statement::Return(..) => "return ...".to_string(),
+            Statement::Expression(..) => "...".to_string(),
+        }
+    }
+}
+
+impl Hook for Tracer {
+    fn after_statement(
+        &mut self,
+        interpreter: &mut Interpreter,
+        statement: &Statement,
+        value: &Object,
+    ) {
+        let position = match statement.position() {
+            Some(position) if self.traces_frame(interpreter) => position,
+            _ => return,
+        };
+        let message = format!("{} => {}", self.describe(statement, position), value);
+        self.log(position, interpreter.frames().len(), &message);
+    }
+
+    fn enter_function(&mut self, interpreter: &mut Interpreter, name: &str, position: &Position) {
+        // The new frame is already pushed, the caller's is below it.
+        let depth = interpreter.frames().len() - 1;
+        let caller = depth
+            .checked_sub(1)
+            .map_or("<main>", |index| interpreter.frames()[index].name.as_str());
+        if !self.traces(name) && !self.traces(caller) {
+            return;
+        }
+
+        let arguments: Vec<String> = interpreter
+            .functions()
+            .into_iter()
+            .find(|function| function.identifier.lexeme == name)
+            .map(|function| {
+                let variables = interpreter.variables();
+                function
+                    .paramiters
+                    .iter()
+                    .filter_map(|paramiter| {
+                        variables
+                            .iter()
+                            .find(|(variable, _)| *variable == paramiter.lexeme)
+                            .map(|(variable, value)| format!("{} = {}", variable, value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let message = format!("-> {}({})", name, arguments.join(", "));
+        self.log(position, depth, &message);
+    }
+
+    fn after_expression(
+        &mut self,
+        interpreter: &mut Interpreter,
+        expression: &Expression,
+        value: &Object,
+    ) {
+        let call_expression = match expression {
+            Expression::Call(call_expression) => call_expression,
+            _ => return,
+        };
+        let name = call_expression.identifier.lexeme.as_str();
+        let user_function = interpreter
+            .functions()
+            .iter()
+            .any(|function| function.identifier.lexeme == name);
+        if user_function && (self.traces(name) || self.traces_frame(interpreter)) {
+            let message = format!("<- {} = {}", name, value);
+            self.log(
+                &call_expression.identifier.position,
+                interpreter.frames().len(),
+                &message,
+            );
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// Fixtures shared by the tests that run programs inside the test process.
+#![allow(dead_code)]
+
+use std::{cell::RefCell, io, rc::Rc};
+
+use func::{
+    common::ast::Program,
+    frontend::{lexer::Lexer, parser::Parser},
+};
+
+// An output that can still be read after a clone of it was given to the interpreter.
+#[derive(Clone, Default)]
+pub struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Buffer {
+    // Takes what was written so far.
+    pub fn take(&self) -> String {
+        String::from_utf8(self.0.take()).unwrap()
+    }
+}
+
+impl io::Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Lexes and parses `source`, which must be valid, as the file `test`.
+pub fn parse(source: &str) -> Program {
+    let tokens = Lexer::new("test".to_string(), source).lex().unwrap();
+    Parser::new(tokens).parse().unwrap()
+}
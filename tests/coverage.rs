@@ -0,0 +1,109 @@
+// Records coverage of a small program and checks the counts and the LCOV it writes.
+
+mod common;
+
+use std::{cell::RefCell, env::temp_dir, fs, io::empty, process::Command, rc::Rc};
+
+use func::runtime::{coverage::Coverage, interpreter::Interpreter};
+
+use common::parse;
+
+const SOURCE: &str = "func sign(n) {
+    if n < 0 {
+        -1
+    } else if n == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+func unused() {
+    write(\"never\")
+}
+
+test \"signs\" {
+    assert_eq(sign(5), 1)
+    assert_eq(sign(-2), -1)
+}
+";
+
+#[test]
+fn coverage_counts_lines_and_branches() {
+    let program = parse(SOURCE);
+    let mut coverage = Coverage::new();
+    coverage.add_program(&program);
+    let coverage = Rc::new(RefCell::new(coverage));
+    let results = Interpreter::with_io(empty(), Vec::new())
+        .with_hook(coverage.clone())
+        .run_tests(program)
+        .unwrap();
+    assert!(results.iter().all(|result| result.error.is_none()));
+
+    let coverage = coverage.borrow();
+    let file = &coverage.files["test"];
+    let lines: Vec<(usize, usize)> = file
+        .lines
+        .iter()
+        .map(|(line, hits)| (*line, *hits))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            (1, 1),
+            (2, 2),
+            (3, 1),
+            (5, 0),
+            (7, 1),
+            (11, 1),
+            (12, 0),
+            (16, 1),
+            (17, 1)
+        ]
+    );
+    assert_eq!(file.missed(), "5, 12");
+    let branches: Vec<[usize; 2]> = file.branches.values().copied().collect();
+    assert_eq!(branches, vec![[1, 1], [0, 1]]);
+
+    let lcov = coverage.lcov();
+    assert!(lcov.starts_with("TN:\nSF:test\n"), "{}", lcov);
+    for record in [
+        "BRDA:4,0,0,0",
+        "BRDA:4,0,1,1",
+        "BRF:4",
+        "BRH:3",
+        "DA:5,0",
+        "LF:9",
+        "LH:7",
+    ] {
+        assert!(
+            lcov.lines().any(|line| line == record),
+            "missing {}:\n{}",
+            record,
+            lcov
+        );
+    }
+    assert!(lcov.ends_with("end_of_record\n"));
+}
+
+#[test]
+fn test_command_writes_lcov() {
+    let dir = temp_dir().join(format!("func_coverage_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sign.func"), SOURCE).unwrap();
+    let lcov = dir.join("lcov.info");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_func"))
+        .arg("test")
+        .arg(format!("--coverage={}", lcov.display()))
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let summary = String::from_utf8(output.stderr).unwrap();
+    assert!(summary.contains("7/9"), "{}", summary);
+    assert!(summary.contains("3/4"), "{}", summary);
+    assert!(fs::read_to_string(&lcov).unwrap().contains("sign.func"));
+
+    fs::remove_dir_all(&dir).ok();
+}
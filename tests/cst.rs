@@ -0,0 +1,102 @@
+// Builds the lossless syntax tree of every example and golden script and checks that it
+// gives back the source byte for byte and lowers to the same program the parser produces.
+
+use std::{
+    ffi::OsStr,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use func::{
+    common::token::{Token, TokenType},
+    frontend::{
+        cst::{SyntaxElement, SyntaxKind, SyntaxNode},
+        lexer::Lexer,
+        parser::Parser,
+    },
+};
+
+fn scripts() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = Vec::new();
+    for dir in [
+        root.join("examples").join("syntax"),
+        root.join("tests").join("golden"),
+    ] {
+        files.extend(
+            read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension() == Some(OsStr::new("func"))),
+        );
+    }
+    files.sort();
+    files
+}
+
+// Golden scripts that test lexing and parsing errors on purpose.
+const UNPARSABLE: &[&str] = &["18_lexing_error.func", "19_parsing_error.func"];
+
+#[test]
+fn syntax_tree_round_trips_source() {
+    let mut unparsable = Vec::new();
+    for path in scripts() {
+        let source = read_to_string(&path).unwrap();
+        match SyntaxNode::parse("test", &source) {
+            Ok(tree) => assert_eq!(tree.text(), source, "{} didn't round trip", path.display()),
+            Err(..) => unparsable.push(path.file_name().unwrap().to_string_lossy().into_owned()),
+        }
+    }
+    assert_eq!(unparsable, UNPARSABLE);
+}
+
+#[test]
+fn syntax_tree_lowers_to_parsed_program() {
+    for path in scripts() {
+        let source = read_to_string(&path).unwrap();
+        let parsed = Lexer::new("test".to_string(), &source)
+            .lex()
+            .and_then(|tokens| Parser::new(tokens).parse());
+        let lowered = SyntaxNode::parse("test", &source).and_then(|tree| tree.to_program());
+        assert_eq!(
+            format!("{:?}", lowered),
+            format!("{:?}", parsed),
+            "{} lowered differently",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn syntax_tree_attaches_trivia() {
+    let source = "// add\nlet x = 1 + 2 // three\n\n  x\n";
+    let tree = SyntaxNode::parse("test", source).unwrap();
+
+    let kinds: Vec<SyntaxKind> = tree.nodes().map(|node| node.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![SyntaxKind::LetStatement, SyntaxKind::IdentifierExpression]
+    );
+    let let_statement = tree.nodes().next().unwrap();
+    assert!(let_statement.nodes().any(|node| {
+        node.kind == SyntaxKind::BinaryExpression
+            && node
+                .nodes()
+                .all(|operand| operand.kind == SyntaxKind::LiteralExpression)
+    }));
+
+    let tokens = tree.tokens();
+    let lexemes =
+        |trivia: &[Token]| -> String { trivia.iter().map(|token| token.lexeme.as_str()).collect() };
+    assert_eq!(tokens[0].token.lexeme, "let");
+    assert_eq!(lexemes(&tokens[0].leading), "// add\n");
+    assert_eq!(tokens[5].token.lexeme, "2");
+    assert_eq!(lexemes(&tokens[5].trailing), " // three");
+    assert_eq!(tokens[6].token.lexeme, "x");
+    assert_eq!(lexemes(&tokens[6].leading), "\n\n  ");
+    assert_eq!(lexemes(&tokens[7].leading), "\n");
+    assert!(matches!(
+        tree.children.last(),
+        Some(SyntaxElement::Token(token)) if token.token.ttype == TokenType::EOF
+    ));
+}
@@ -0,0 +1,225 @@
+// Drives `func dap` the way an editor does, waiting for `stopped` events before inspecting
+// the paused program.
+
+use std::{
+    env::temp_dir,
+    fs::{remove_file, write},
+    io::{BufRead, BufReader, Read, Write},
+    process::{ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use func::common::json::Value;
+
+const SOURCE: &str = "func double(n) {
+    let result = n * 2
+    result
+}
+
+let total = double(21)
+write(total, \"\\n\")
+";
+
+struct Client {
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    seq: usize,
+    messages: Vec<Value>,
+}
+
+impl Client {
+    fn request(&mut self, command: &str, arguments: &str) -> usize {
+        self.seq += 1;
+        let body = format!(
+            r#"{{"seq":{},"type":"request","command":"{}","arguments":{}}}"#,
+            self.seq, command, arguments
+        );
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.seq
+    }
+
+    // Reads messages until one for which `done` holds, and gives it back.
+    fn until(&mut self, done: impl Fn(&Value) -> bool) -> Value {
+        loop {
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                assert!(
+                    self.output.read_line(&mut header).unwrap() > 0,
+                    "{:?}",
+                    self.messages
+                );
+                match header.trim_end().strip_prefix("Content-Length: ") {
+                    Some(value) => length = value.parse().unwrap(),
+                    None if header.trim_end().is_empty() => break,
+                    None => {}
+                }
+            }
+            let mut body = vec![0; length];
+            self.output.read_exact(&mut body).unwrap();
+            let message = Value::parse(&String::from_utf8(body).unwrap()).unwrap();
+            self.messages.push(message.clone());
+            if done(&message) {
+                return message;
+            }
+        }
+    }
+
+    fn response(&mut self, seq: usize) -> Value {
+        let message =
+            self.until(|message| message.get("request_seq") == Some(&Value::Number(seq as f64)));
+        assert_eq!(
+            message.get("success"),
+            Some(&Value::Bool(true)),
+            "{}",
+            message
+        );
+        message.get("body").cloned().unwrap_or(Value::Null)
+    }
+
+    fn event(&mut self, event: &str) -> Value {
+        self.until(|message| message.get("event").and_then(Value::as_str) == Some(event))
+    }
+}
+
+#[test]
+fn debug_adapter_session() {
+    let path = temp_dir().join(format!("func_dap_{}.func", std::process::id()));
+    write(&path, SOURCE).unwrap();
+    let path = path.to_str().unwrap().to_string();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_func"))
+        .arg("dap")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut client = Client {
+        input: child.stdin.take().unwrap(),
+        output: BufReader::new(child.stdout.take().unwrap()),
+        seq: 0,
+        messages: Vec::new(),
+    };
+
+    let initialize = client.request("initialize", r#"{"adapterID":"func"}"#);
+    client.response(initialize);
+    client.event("initialized");
+    let launch = client.request(
+        "launch",
+        &format!(r#"{{"program":{}}}"#, Value::from(path.as_str())),
+    );
+    client.response(launch);
+    let breakpoints = client.request(
+        "setBreakpoints",
+        &format!(
+            r#"{{"source":{{"path":{}}},"breakpoints":[{{"line":3}}]}}"#,
+            Value::from(path.as_str())
+        ),
+    );
+    assert_eq!(
+        client
+            .response(breakpoints)
+            .pointer("breakpoints")
+            .and_then(Value::as_array)
+            .map(<[Value]>::len),
+        Some(1)
+    );
+    let done = client.request("configurationDone", "{}");
+    client.response(done);
+
+    let stopped = client.event("stopped");
+    assert_eq!(
+        stopped.pointer("body.reason").and_then(Value::as_str),
+        Some("breakpoint")
+    );
+
+    let trace = client.request("stackTrace", r#"{"threadId":1}"#);
+    let frames = client.response(trace);
+    let frames = frames.get("stackFrames").and_then(Value::as_array).unwrap();
+    let names: Vec<&str> = frames
+        .iter()
+        .filter_map(|frame| frame.get("name").and_then(Value::as_str))
+        .collect();
+    assert_eq!(names, vec!["double", "<main>"]);
+    assert_eq!(frames[0].get("line"), Some(&Value::Number(3.0)));
+    assert_eq!(frames[1].get("line"), Some(&Value::Number(6.0)));
+
+    let scopes = client.request("scopes", r#"{"frameId":0}"#);
+    let reference = client
+        .response(scopes)
+        .pointer("scopes")
+        .and_then(Value::as_array)
+        .unwrap()[0]
+        .get("variablesReference")
+        .cloned()
+        .unwrap();
+    let variables = client.request(
+        "variables",
+        &format!(r#"{{"variablesReference":{}}}"#, reference),
+    );
+    let variables = client.response(variables);
+    let variables: Vec<(String, String)> = variables
+        .get("variables")
+        .and_then(Value::as_array)
+        .unwrap()
+        .iter()
+        .map(|variable| {
+            (
+                variable
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap()
+                    .to_string(),
+                variable
+                    .get("value")
+                    .and_then(Value::as_str)
+                    .unwrap()
+                    .to_string(),
+            )
+        })
+        .collect();
+    assert!(
+        variables.contains(&("n".to_string(), "21".to_string())),
+        "{:?}",
+        variables
+    );
+    assert!(
+        variables.contains(&("result".to_string(), "42".to_string())),
+        "{:?}",
+        variables
+    );
+
+    let evaluate = client.request("evaluate", r#"{"expression":"result + n","frameId":0}"#);
+    assert_eq!(
+        client
+            .response(evaluate)
+            .get("result")
+            .and_then(Value::as_str),
+        Some("63")
+    );
+
+    let step_out = client.request("stepOut", r#"{"threadId":1}"#);
+    client.response(step_out);
+    client.event("stopped");
+    let trace = client.request("stackTrace", r#"{"threadId":1}"#);
+    let frames = client.response(trace);
+    let frames = frames.get("stackFrames").and_then(Value::as_array).unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].get("line"), Some(&Value::Number(7.0)));
+
+    let resume = client.request("continue", r#"{"threadId":1}"#);
+    client.response(resume);
+    let output = client.event("output");
+    assert_eq!(
+        output.pointer("body.output").and_then(Value::as_str),
+        Some("42\n")
+    );
+    let exited = client.event("exited");
+    assert_eq!(exited.pointer("body.exitCode"), Some(&Value::Number(0.0)));
+    client.event("terminated");
+
+    let disconnect = client.request("disconnect", "{}");
+    client.response(disconnect);
+    drop(client);
+    assert_eq!(child.wait().unwrap().code(), Some(0));
+    remove_file(&path).ok();
+}
@@ -0,0 +1,79 @@
+// Drives `func debug` with a scripted session and checks where it pauses and what it prints.
+
+use std::{
+    env::temp_dir,
+    fs::write,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const SOURCE: &str = "func fib(n) {
+    if n < 2 {
+        n
+    } else {
+        let a = fib(n - 1)
+        a + fib(n - 2)
+    }
+}
+
+let result = fib(4)
+write(result, \"\\n\")
+";
+
+fn session(name: &str, commands: &str) -> (String, i32) {
+    let path = temp_dir().join(format!("func_{}_{}.func", name, std::process::id()));
+    write(&path, SOURCE).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_func"))
+        .arg("debug")
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&path).ok();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.code().unwrap(),
+    )
+}
+
+#[test]
+fn debugger_stops_at_breakpoints_and_inspects_frames() {
+    let (output, status) = session("breakpoints", "b 3\nc\nbt\nv 1\np n + 10\no\nv\nd 3\nc\n");
+    assert_eq!(status, 0);
+
+    let pauses: Vec<&str> = output
+        .lines()
+        .filter_map(|line| line.split("Paused in ").nth(1))
+        .map(|line| line.rsplit(':').next().unwrap())
+        .collect();
+    assert_eq!(pauses, vec!["1", "3", "6"]);
+
+    assert!(output.contains("#0 fib at"), "{}", output);
+    assert!(output.contains("#4 <main> at"), "{}", output);
+    assert!(output.contains("(debug) args = []\nn = 2\n"), "{}", output);
+    assert!(output.contains("(debug) 11\n"), "{}", output);
+    assert!(output.contains("a = 1\n"), "{}", output);
+    assert!(output.ends_with("3\nProgram finished.\n"), "{}", output);
+}
+
+#[test]
+fn debugger_steps_over_calls_and_quits() {
+    let (output, status) = session("next", "n\nn\nq\n");
+    assert_eq!(status, 0);
+    assert!(
+        output.contains(">   10 | let result = fib(4)"),
+        "{}",
+        output
+    );
+    assert!(output.contains(">   11 | write(result"), "{}", output);
+    assert!(!output.contains("Paused in fib"), "{}", output);
+    assert!(!output.contains("Program finished."), "{}", output);
+}
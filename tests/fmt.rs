@@ -0,0 +1,117 @@
+// Formats every example in `examples/syntax` and checks that formatting is idempotent,
+// keeps every comment and doesn't change the tokens the parser sees.
+
+use std::{
+    ffi::OsStr,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use func::{
+    common::token::{Token, TokenType},
+    frontend::{formatter::Formatter, lexer::Lexer},
+};
+
+fn examples() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = read_dir(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples")
+            .join("syntax"),
+    )
+    .expect("examples/syntax should exist")
+    .map(|entry| entry.unwrap().path())
+    .filter(|path| path.extension() == Some(OsStr::new("func")))
+    .collect();
+    files.sort();
+    files
+}
+
+fn lex(source: &str) -> Vec<Token> {
+    Lexer::new("test".to_string(), source)
+        .with_comments()
+        .lex()
+        .unwrap()
+}
+
+fn format(source: &str) -> String {
+    Formatter::new(lex(source)).format()
+}
+
+fn lexemes(source: &str, comments: bool) -> Vec<String> {
+    lex(source)
+        .into_iter()
+        .filter(|token| (token.ttype == TokenType::Comment) == comments)
+        .map(|token| token.lexeme.trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    for path in examples() {
+        let formatted = format(&read_to_string(&path).unwrap());
+        assert_eq!(
+            format(&formatted),
+            formatted,
+            "formatting {} twice changed it",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn formatting_keeps_tokens_and_comments() {
+    for path in examples() {
+        let source = read_to_string(&path).unwrap();
+        let formatted = format(&source);
+        assert_eq!(
+            lexemes(&formatted, false),
+            lexemes(&source, false),
+            "formatting {} changed its tokens",
+            path.display()
+        );
+        assert_eq!(
+            lexemes(&formatted, true),
+            lexemes(&source, true),
+            "formatting {} changed its comments",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn formatting_canonicalizes_blocks_and_spacing() {
+    let source = "\
+// leading
+func   add(a,b)
+{
+  return a+b   // trailing
+}
+
+
+if add(1,2)==3
+{
+write( \"yes\" ,-1, 2 - -3)
+}
+else
+{
+    // inside
+  write(\"no\")
+}";
+
+    assert_eq!(
+        format(source),
+        "\
+// leading
+func add(a, b) {
+    return a + b // trailing
+}
+
+if add(1, 2) == 3 {
+    write(\"yes\", -1, 2 - -3)
+} else {
+    // inside
+    write(\"no\")
+}
+"
+    );
+}
@@ -0,0 +1,105 @@
+// Runs scripts that use the file builtins in a scratch directory and checks what they
+// print and leave behind.
+
+mod common;
+
+use std::{env, fs, io, path::PathBuf, process};
+
+use func::{
+    common::error::{code, Error},
+    runtime::{interpreter::Interpreter, limits::Capabilities},
+};
+
+use common::{parse, Buffer};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("func_fs_{}_{}", name, process::id()));
+    fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+fn run(source: &str, capabilities: Capabilities) -> Result<String, Error> {
+    let program = parse(source);
+    let output = Buffer::default();
+    Interpreter::with_io(io::empty(), output.clone())
+        .with_capabilities(capabilities)
+        .interpret(program)?;
+    Ok(output.take())
+}
+
+#[test]
+fn file_builtins_read_and_change_the_file_system() {
+    let dir = scratch("builtins");
+    let source = format!(
+        "let dir = \"{}\"
+mkdir(dir + \"/logs\")
+write_file(dir + \"/notes.txt\", \"one\\n\")
+append_file(dir + \"/notes.txt\", 2)
+write(read_lines(dir + \"/notes.txt\"), \" \", list_dir(dir), \"\\n\")
+rename(dir + \"/notes.txt\", dir + \"/old.txt\")
+remove_file(dir + \"/logs\")
+write(exists(dir + \"/notes.txt\"), \" \", list_dir(dir), \"\\n\")
+",
+        dir.display()
+    );
+
+    let output = run(&source, Capabilities::default()).unwrap();
+    assert_eq!(output, "[one,2,] [logs,notes.txt,]\nfalse [old.txt,]\n");
+    assert_eq!(fs::read_to_string(dir.join("old.txt")).unwrap(), "one\n2");
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn file_errors_can_be_caught() {
+    let dir = scratch("errors");
+    let source = format!(
+        "func fallback(message) {{ starts_with(message, \"Could not read\") }}
+write(try(read_file, fallback, \"{}/missing.txt\"), \" \", try(upper, fallback, \"ok\"))
+",
+        dir.display()
+    );
+    assert_eq!(run(&source, Capabilities::default()).unwrap(), "true OK");
+
+    let err = run("read_file(\"missing.txt\")\n", Capabilities::default()).unwrap_err();
+    assert_eq!(err.code(), code::IO_FAILURE);
+}
+
+#[test]
+fn capabilities_deny_file_access() {
+    let dir = scratch("sandbox");
+    let source = format!("write_file(\"{}\", \"x\")\n", dir.display());
+    let err = run(&source, Capabilities::none()).unwrap_err();
+    assert_eq!(err.code(), code::NOT_ALLOWED);
+    assert!(!dir.exists());
+}
+
+#[test]
+fn remove_only_takes_arrays() {
+    let dir = scratch("remove");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("keep.txt");
+    fs::write(&path, "keep").unwrap();
+    let source = format!("let path = \"{}\"\nremove(path, 0)\n", path.display());
+    let err = run(&source, Capabilities::default()).unwrap_err();
+    assert_eq!(err.code(), code::ARGUMENT_TYPE);
+    assert!(path.exists());
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn written_text_reads_back_the_same() {
+    let dir = scratch("round_trip");
+    let source = format!(
+        "let path = \"{}\"
+mkdir(path)
+path = path + \"/lines.txt\"
+let text = \"one\\ntwo\\n\"
+write_file(path, text)
+write(read_file(path) == text, \" \", split(read_file(path), \"\\n\"), \" \", read_lines(path))
+",
+        dir.display()
+    );
+    let output = run(&source, Capabilities::default()).unwrap();
+    assert_eq!(output, "true [one,two,,] [one,two,]");
+    fs::remove_dir_all(&dir).ok();
+}
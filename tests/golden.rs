@@ -0,0 +1,150 @@
+// Runs every `.func` file in `tests/golden` through the `func` binary and compares
+// what it prints against the `.out` (stdout) and `.err` (stderr) files next to it,
+// and its exit status against the `.code` file. A `.in` file, if present, is fed to
+// the script's stdin and a `.args` file replaces the command line (by default just the
+// file name). Scripts run with `FUNC_GOLDEN=1` in their environment. A missing `.out`
+// or `.err` file means the stream is expected to be empty, a missing `.code` file
+// means the exit status is expected to be 0.
+//
+// Run with `UPDATE_GOLDEN=1 cargo test --test golden` to rewrite the expectations.
+
+use std::{
+    env,
+    ffi::OsStr,
+    fs::{self, read_dir},
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+}
+
+fn golden_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = read_dir(golden_dir())
+        .expect("tests/golden should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some(OsStr::new("func")))
+        .collect();
+    files.sort();
+    files
+}
+
+fn run(source: &Path) -> (String, String, String) {
+    let input = fs::read(source.with_extension("in")).unwrap_or_default();
+    let args = match fs::read_to_string(source.with_extension("args")) {
+        Ok(args) => args.split_whitespace().map(String::from).collect(),
+        Err(_) => vec![source.file_name().unwrap().to_string_lossy().into_owned()],
+    };
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_func"))
+        .args(args)
+        .env("FUNC_GOLDEN", "1")
+        .current_dir(golden_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn func");
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let code = match output.status.code() {
+        Some(0) => String::new(),
+        Some(code) => format!("{}\n", code),
+        None => "signal\n".to_string(),
+    };
+
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+        code,
+    )
+}
+
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the back.
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut output = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            output += &format!("  {}\n", expected[i]);
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            output += &format!("+ {}\n", actual[j]);
+            j += 1;
+        } else {
+            output += &format!("- {}\n", expected[i]);
+            i += 1;
+        }
+    }
+    output
+}
+
+fn check(path: &Path, actual: &str, update: bool, failures: &mut Vec<String>) {
+    if update {
+        if actual.is_empty() {
+            fs::remove_file(path).ok();
+        } else {
+            fs::write(path, actual).unwrap();
+        }
+        return;
+    }
+
+    let expected = fs::read_to_string(path).unwrap_or_default();
+    if expected != actual {
+        failures.push(format!(
+            "{} does not match:\n{}",
+            path.display(),
+            diff(&expected, actual)
+        ));
+    }
+}
+
+#[test]
+fn golden() {
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+
+    for source in golden_files() {
+        let (stdout, stderr, code) = run(&source);
+        check(
+            &source.with_extension("out"),
+            &stdout,
+            update,
+            &mut failures,
+        );
+        check(
+            &source.with_extension("err"),
+            &stderr,
+            update,
+            &mut failures,
+        );
+        check(&source.with_extension("code"), &code, update, &mut failures);
+    }
+
+    if !failures.is_empty() {
+        panic!(
+            "{} golden file(s) differ (rerun with UPDATE_GOLDEN=1 to accept):\n\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Runs programs past each limit and capability and checks the error they stop with.
+
+mod common;
+
+use std::{io, thread, time::Duration};
+
+use func::{
+    common::error::{code, Error},
+    runtime::{
+        interpreter::Interpreter,
+        limits::{Capabilities, Limits},
+    },
+};
+
+use common::{parse, Buffer};
+
+const FIB: &str = "func fib(n) {
+    if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+}
+write(fib(15))
+";
+
+fn run(source: &str, limits: Limits, capabilities: Capabilities) -> Result<String, Error> {
+    let program = parse(source);
+    let output = Buffer::default();
+    Interpreter::with_io(io::Cursor::new("input\n"), output.clone())
+        .with_capabilities(capabilities)
+        .with_limits(limits)
+        .interpret(program)?;
+    Ok(output.take())
+}
+
+fn error_code(source: &str, limits: Limits, capabilities: Capabilities) -> &'static str {
+    run(source, limits, capabilities).unwrap_err().code()
+}
+
+#[test]
+fn programs_within_limits_run() {
+    let limits = Limits {
+        steps: Some(1_000_000),
+        depth: Some(20),
+        size: Some(16),
+        time: Some(Duration::from_secs(60)),
+    };
+    assert_eq!(run(FIB, limits, Capabilities::none()).unwrap(), "610");
+}
+
+#[test]
+fn limits_stop_runaway_programs() {
+    let steps = Limits {
+        steps: Some(1000),
+        ..Limits::default()
+    };
+    assert_eq!(
+        error_code(FIB, steps, Capabilities::default()),
+        code::STEP_LIMIT
+    );
+
+    let depth = Limits {
+        depth: Some(100),
+        ..Limits::default()
+    };
+    let runaway = "func f(n) { f(n + 1) }\nf(0)\n";
+    assert_eq!(
+        error_code(runaway, depth, Capabilities::default()),
+        code::DEPTH_LIMIT
+    );
+
+    let size = Limits {
+        size: Some(8),
+        ..Limits::default()
+    };
+    let strings = "let s = \"abc\"\ns = s + s\ns = s + s\n";
+    assert_eq!(
+        error_code(strings, size.clone(), Capabilities::default()),
+        code::SIZE_LIMIT
+    );
+    let arrays = "let a = [1, 2, 3, 4, 5, 6, 7, 8]\npush(9, a)\n";
+    assert_eq!(
+        error_code(arrays, size, Capabilities::default()),
+        code::SIZE_LIMIT
+    );
+
+    let time = Limits {
+        time: Some(Duration::from_millis(1)),
+        ..Limits::default()
+    };
+    let slow = FIB.replace("15", "40");
+    assert_eq!(
+        error_code(&slow, time, Capabilities::default()),
+        code::TIME_LIMIT
+    );
+}
+
+#[test]
+fn strings_too_large_to_allocate_are_errors() {
+    for source in [
+        "repeat(\"ab\", 1000000000000000)\n",
+        "repeat(\"ab\", 9000000000000000000)\n",
+        "pad_left(\"a\", 1000000000000000000)\n",
+        "pad_right(\"a\", 1000000000000000000, \"é\")\n",
+    ] {
+        assert_eq!(
+            error_code(source, Limits::default(), Capabilities::default()),
+            code::SIZE_LIMIT,
+            "{}",
+            source
+        );
+    }
+    let size = Limits {
+        size: Some(8),
+        ..Limits::default()
+    };
+    assert_eq!(
+        error_code("repeat(\"ab\", 5)\n", size, Capabilities::default()),
+        code::SIZE_LIMIT
+    );
+}
+
+#[test]
+fn ranges_too_large_to_allocate_are_errors() {
+    for source in [
+        "range(1000000000000000000)\n",
+        "range(0, 1, 0.0000000000000000001)\n",
+    ] {
+        assert_eq!(
+            error_code(source, Limits::default(), Capabilities::default()),
+            code::SIZE_LIMIT,
+            "{}",
+            source
+        );
+    }
+    let size = Limits {
+        size: Some(8),
+        ..Limits::default()
+    };
+    assert_eq!(
+        error_code("range(9)\n", size.clone(), Capabilities::default()),
+        code::SIZE_LIMIT
+    );
+    assert_eq!(
+        run("write(range(8))\n", size, Capabilities::default()).unwrap(),
+        "[0,1,2,3,4,5,6,7,]"
+    );
+}
+
+#[test]
+fn capabilities_deny_access() {
+    let read = "let name = \"\"\nread(name)\nwrite(name)\n";
+    assert_eq!(
+        run(read, Limits::default(), Capabilities::default()).unwrap(),
+        "input"
+    );
+    assert_eq!(
+        error_code(read, Limits::default(), Capabilities::none()),
+        code::NOT_ALLOWED
+    );
+    assert_eq!(
+        error_code("env(\"HOME\")\n", Limits::default(), Capabilities::none()),
+        code::NOT_ALLOWED
+    );
+}
+
+#[test]
+fn interrupts_stop_the_running_program() {
+    let program = parse(&FIB.replace("15", "40"));
+    let mut interpreter = Interpreter::with_io(io::empty(), Buffer::default());
+    let handle = interpreter.interrupt_handle();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+    let err = interpreter.interpret(program).unwrap_err();
+    interrupter.join().unwrap();
+    assert_eq!(err.code(), code::INTERRUPTED);
+
+    assert!(interpreter.interpret(parse("1 + 1\n")).is_ok());
+}
+
+#[test]
+fn interrupts_while_idle_are_dropped() {
+    let mut interpreter = Interpreter::with_io(io::empty(), Buffer::default());
+    interpreter.interrupt_handle().interrupt();
+    assert!(interpreter.interpret(parse("1 + 1\n")).is_ok());
+}
+
+#[test]
+fn try_catches_errors_but_not_limits() {
+    let handler = "func handler(message) { \"caught: \" + message }\n";
+    let fail = format!(
+        "{}func fail(x) {{ x + \"!\" }}\nwrite(try(fail, handler, 1))\n",
+        handler
+    );
+    let output = run(&fail, Limits::default(), Capabilities::default()).unwrap();
+    assert!(output.starts_with("caught: Type mismatch"), "{}", output);
+
+    let denied = format!("{}write(try(env, handler, \"HOME\"))\n", handler);
+    let output = run(&denied, Limits::default(), Capabilities::none()).unwrap();
+    assert!(
+        output.starts_with("caught: `env` is not allowed"),
+        "{}",
+        output
+    );
+
+    let depth = Limits {
+        depth: Some(100),
+        ..Limits::default()
+    };
+    let runaway = format!("{}func f(n) {{ f(n + 1) }}\ntry(f, handler, 0)\n", handler);
+    assert_eq!(
+        error_code(&runaway, depth, Capabilities::default()),
+        code::DEPTH_LIMIT
+    );
+}
@@ -0,0 +1,249 @@
+// Drives `func lsp` through a whole session over pipes and checks its answers.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use func::common::json::Value;
+
+const SOURCE: &str =
+    "func add(a, b) {\n    a + b\n}\n\nlet total = add(1, 2)\nwrite(total, missing)\n";
+const URI: &str = "file:///tmp/main.func";
+
+fn frame(message: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", message.len(), message)
+}
+
+fn request(id: usize, method: &str, params: &str) -> String {
+    frame(&format!(
+        r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{}}}"#,
+        id, method, params
+    ))
+}
+
+fn at(line: usize, character: usize, extra: &str) -> String {
+    format!(
+        r#"{{"textDocument":{{"uri":"{}"}},"position":{{"line":{},"character":{}}}{}}}"#,
+        URI, line, character, extra
+    )
+}
+
+fn session(input: &str) -> (Vec<Value>, i32) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_func"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let mut messages = Vec::new();
+    let mut rest = String::from_utf8(output.stdout).unwrap();
+    while let Some(start) = rest.find("\r\n\r\n") {
+        let length: usize = rest["Content-Length: ".len()..start].parse().unwrap();
+        let body = rest[start + 4..start + 4 + length].to_string();
+        messages.push(Value::parse(&body).expect("responses are JSON"));
+        rest = rest[start + 4 + length..].to_string();
+    }
+    (messages, output.status.code().unwrap())
+}
+
+fn response(messages: &[Value], id: usize) -> &Value {
+    messages
+        .iter()
+        .find(|message| message.get("id") == Some(&Value::Number(id as f64)))
+        .and_then(|message| message.get("result"))
+        .unwrap_or_else(|| panic!("no result for request {}", id))
+}
+
+#[test]
+fn language_server_session() {
+    let mut input = request(1, "initialize", "{}");
+    input += &frame(&format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{}","languageId":"func","version":1,"text":{}}}}}}}"#,
+        URI,
+        Value::from(SOURCE)
+    ));
+    input += &request(2, "textDocument/definition", &at(4, 13, ""));
+    input += &request(3, "textDocument/hover", &at(4, 13, ""));
+    input += &request(4, "textDocument/documentSymbol", &at(0, 0, ""));
+    input += &request(5, "textDocument/completion", &at(5, 0, ""));
+    input += &request(6, "textDocument/rename", &at(1, 4, r#","newName":"x""#));
+    input += &request(7, "shutdown", "null");
+    input += &frame(r#"{"jsonrpc":"2.0","method":"exit"}"#);
+
+    let (messages, status) = session(&input);
+    assert_eq!(status, 0);
+
+    let capabilities = response(&messages, 1).get("capabilities").unwrap();
+    assert_eq!(capabilities.get("renameProvider"), Some(&Value::Bool(true)));
+
+    let diagnostics = messages
+        .iter()
+        .find(|message| {
+            message.get("method").and_then(Value::as_str) == Some("textDocument/publishDiagnostics")
+        })
+        .and_then(|message| message.pointer("params.diagnostics"))
+        .and_then(Value::as_array)
+        .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].get("code").and_then(Value::as_str),
+        Some("E0301")
+    );
+    assert_eq!(
+        diagnostics[0].pointer("range.start.line"),
+        Some(&Value::Number(5.0))
+    );
+
+    let definition = response(&messages, 2);
+    assert_eq!(
+        definition.pointer("range.start.line"),
+        Some(&Value::Number(0.0))
+    );
+    assert_eq!(
+        definition.pointer("range.start.character"),
+        Some(&Value::Number(5.0))
+    );
+
+    let hover = response(&messages, 3)
+        .pointer("contents.value")
+        .and_then(Value::as_str)
+        .unwrap();
+    assert!(hover.contains("func add(a, b)"), "{}", hover);
+
+    let symbols: Vec<&str> = response(&messages, 4)
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|symbol| symbol.get("name").and_then(Value::as_str))
+        .collect();
+    assert_eq!(symbols, vec!["add", "total"]);
+
+    let labels: Vec<&str> = response(&messages, 5)
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|item| item.get("label").and_then(Value::as_str))
+        .collect();
+    for label in ["add", "total", "let", "write", "env"] {
+        assert!(labels.contains(&label), "missing completion {}", label);
+    }
+
+    let edits = response(&messages, 6)
+        .get("changes")
+        .and_then(|changes| changes.get(URI))
+        .and_then(Value::as_array)
+        .unwrap();
+    let lines: Vec<&Value> = edits
+        .iter()
+        .filter_map(|edit| edit.pointer("range.start.line"))
+        .collect();
+    assert_eq!(lines, vec![&Value::Number(0.0), &Value::Number(1.0)]);
+}
+
+#[test]
+fn calls_at_the_start_of_a_block_are_not_declarations() {
+    let source = "func show(x) {\n    write(x)\n}\n\nif 1 == 1 {\n    show(2)\n}\n";
+    let mut input = request(1, "initialize", "{}");
+    input += &frame(&format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{}","languageId":"func","version":1,"text":{}}}}}}}"#,
+        URI,
+        Value::from(source)
+    ));
+    input += &request(2, "textDocument/definition", &at(5, 5, ""));
+    input += &request(3, "textDocument/documentSymbol", &at(0, 0, ""));
+    input += &request(4, "shutdown", "null");
+    input += &frame(r#"{"jsonrpc":"2.0","method":"exit"}"#);
+
+    let (messages, status) = session(&input);
+    assert_eq!(status, 0);
+
+    let definition = response(&messages, 2);
+    assert_eq!(
+        definition.pointer("range.start.line"),
+        Some(&Value::Number(0.0))
+    );
+    assert_eq!(
+        definition.pointer("range.start.character"),
+        Some(&Value::Number(5.0))
+    );
+
+    let symbols: Vec<&str> = response(&messages, 3)
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|symbol| symbol.get("name").and_then(Value::as_str))
+        .collect();
+    assert_eq!(symbols, vec!["show"]);
+}
+
+#[test]
+fn functions_passed_as_arguments_are_references() {
+    let source = "func double(x) {\n    x * 2\n}\n\nlet xs = map([1, 2], double)\n";
+    let mut input = request(1, "initialize", "{}");
+    input += &frame(&format!(
+        r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{}","languageId":"func","version":1,"text":{}}}}}}}"#,
+        URI,
+        Value::from(source)
+    ));
+    input += &request(2, "textDocument/definition", &at(4, 23, ""));
+    input += &request(3, "textDocument/rename", &at(0, 6, r#","newName":"twice""#));
+    input += &request(4, "shutdown", "null");
+    input += &frame(r#"{"jsonrpc":"2.0","method":"exit"}"#);
+
+    let (messages, status) = session(&input);
+    assert_eq!(status, 0);
+
+    let definition = response(&messages, 2);
+    assert_eq!(
+        definition.pointer("range.start.line"),
+        Some(&Value::Number(0.0))
+    );
+    assert_eq!(
+        definition.pointer("range.start.character"),
+        Some(&Value::Number(5.0))
+    );
+
+    let edits = response(&messages, 3)
+        .get("changes")
+        .and_then(|changes| changes.get(URI))
+        .and_then(Value::as_array)
+        .unwrap();
+    let starts: Vec<(&Value, &Value)> = edits
+        .iter()
+        .filter_map(|edit| {
+            Some((
+                edit.pointer("range.start.line")?,
+                edit.pointer("range.start.character")?,
+            ))
+        })
+        .collect();
+    assert_eq!(
+        starts,
+        vec![
+            (&Value::Number(0.0), &Value::Number(5.0)),
+            (&Value::Number(4.0), &Value::Number(21.0)),
+        ]
+    );
+}
+
+#[test]
+fn language_server_rejects_unknown_methods() {
+    let mut input = request(1, "workspace/unknown", "{}");
+    input += &frame(r#"{"jsonrpc":"2.0","method":"exit"}"#);
+
+    let (messages, status) = session(&input);
+    assert_eq!(status, 1);
+    assert_eq!(
+        messages[0].pointer("error.code"),
+        Some(&Value::Number(-32601.0))
+    );
+}
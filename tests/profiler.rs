@@ -0,0 +1,73 @@
+// Profiles a small recursive program and checks what the profiler counted.
+
+mod common;
+
+use std::{cell::RefCell, io::empty, rc::Rc};
+
+use func::runtime::{
+    interpreter::Interpreter,
+    profiler::{Profiler, MAIN},
+};
+
+use common::parse;
+
+const SOURCE: &str = "func fib(n) {
+    if n < 2 {
+        n
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+func twice(n) {
+    fib(n) + fib(n)
+}
+
+write(twice(4))
+";
+
+#[test]
+fn profiler_counts_calls_lines_and_stacks() {
+    let program = parse(SOURCE);
+    let profiler = Rc::new(RefCell::new(Profiler::new()));
+    Interpreter::with_io(empty(), Vec::new())
+        .with_hook(profiler.clone())
+        .interpret(program)
+        .unwrap();
+    let mut profiler = profiler.borrow_mut();
+    profiler.finish();
+
+    assert_eq!(profiler.functions[MAIN].calls, 1);
+    assert_eq!(profiler.functions["twice"].calls, 1);
+    // fib(4) makes 9 calls, twice calls it twice.
+    assert_eq!(profiler.functions["fib"].calls, 18);
+    for function in profiler.functions.values() {
+        assert!(function.exclusive <= function.inclusive);
+    }
+    assert!(profiler.functions["twice"].inclusive >= profiler.functions["fib"].inclusive);
+
+    assert_eq!(profiler.lines[&("test".to_string(), 2)].hits, 18);
+    assert_eq!(profiler.lines[&("test".to_string(), 13)].hits, 1);
+
+    let folded = profiler.folded();
+    let stacks: Vec<&str> = folded
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0)
+        .collect();
+    assert!(
+        stacks.contains(&"<main>;twice;fib;fib;fib;fib"),
+        "{}",
+        folded
+    );
+    assert!(!stacks.iter().any(|stack| stack.contains("fib;twice")));
+
+    let summary = profiler.summary(3);
+    assert!(summary.starts_with("function"), "{}", summary);
+    assert_eq!(
+        summary
+            .lines()
+            .filter(|line| line.starts_with("test:"))
+            .count(),
+        3
+    );
+}
@@ -0,0 +1,67 @@
+// Traces a small recursive program into a buffer and checks the log.
+
+mod common;
+
+use std::io;
+
+use func::runtime::{interpreter::Interpreter, tracer::Tracer};
+
+use common::{parse, Buffer};
+
+const SOURCE: &str = "func sum(n) {
+    if n == 0 {
+        return 0
+    }
+    n + sum(n - 1)
+}
+
+let total = sum(2)
+";
+
+fn trace(tracer: impl FnOnce(Tracer) -> Tracer) -> Vec<String> {
+    let program = parse(SOURCE);
+    let buffer = Buffer::default();
+    Interpreter::with_io(io::empty(), Vec::new())
+        .with_hook(tracer(Tracer::new(buffer.clone())))
+        .interpret(program)
+        .unwrap();
+    buffer.take().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn tracer_logs_statements_and_calls() {
+    let log = trace(|tracer| tracer.with_source("test", SOURCE));
+    assert_eq!(
+        log,
+        vec![
+            "test:1: func sum(n) { => nil",
+            "test:8: -> sum(n = 2)",
+            "test:2:   if n == 0 { => nil",
+            "test:5:   -> sum(n = 1)",
+            "test:2:     if n == 0 { => nil",
+            "test:5:     -> sum(n = 0)",
+            "test:3:       return 0 => 0",
+            "test:2:       if n == 0 { => 0",
+            "test:5:     <- sum = 0",
+            "test:5:     n + sum(n - 1) => 1",
+            "test:5:   <- sum = 1",
+            "test:5:   n + sum(n - 1) => 3",
+            "test:8: <- sum = 3",
+            "test:8: let total = sum(2) => 3",
+        ]
+    );
+}
+
+#[test]
+fn tracer_filters_by_function() {
+    let log = trace(|tracer| tracer.with_functions(vec!["<main>".to_string()]));
+    assert_eq!(
+        log,
+        vec![
+            "test:1: func sum => nil",
+            "test:8: -> sum(n = 2)",
+            "test:8: <- sum = 3",
+            "test:8: let total = ... => 3",
+        ]
+    );
+}